@@ -0,0 +1,613 @@
+use alloc::vec::Vec;
+
+use Combine;
+
+/// Extend `Vec<Result<T>>` with a `flip_all` method that, unlike `flip`, doesn't
+/// stop at the first error but collects every error it encounters.
+///
+/// This is useful for validation: the caller can report all the failures at once
+/// instead of making the user fix them one by one.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait FlipAllExt<T, E> {
+
+    /// Flip the collection, returning either all the values or all the errors
+    fn flip_all(self) -> Result<Vec<T>, Vec<E>>;
+
+    /// Like `flip_all`, but collects the errors into a container of the caller's choice
+    fn flip_all_collect<C>(self) -> Result<Vec<T>, C> where
+        C: Default + Extend<E>;
+
+    /// Like `flip_all`, but merges the errors together with `Combine`
+    fn flip_combine(self) -> Result<Vec<T>, E> where
+        E: Combine;
+
+}
+
+impl<T, E> FlipAllExt<T, E> for Vec<Result<T, E>> {
+    fn flip_all(self) -> Result<Vec<T>, Vec<E>>
+    {
+        self.flip_all_collect()
+    }
+
+    fn flip_all_collect<C>(self) -> Result<Vec<T>, C> where
+        C: Default + Extend<E>
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        let mut errors = None;
+        for t in self {
+            match t {
+                Ok(u) => if errors.is_none() { result_vec.push(u) },
+                Err(e) => errors.get_or_insert_with(C::default).extend(Some(e)),
+            }
+        }
+        match errors {
+            None => Ok(result_vec),
+            Some(errors) => Err(errors),
+        }
+    }
+
+    fn flip_combine(self) -> Result<Vec<T>, E> where
+        E: Combine
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        let mut error: Option<E> = None;
+        for t in self {
+            match t {
+                Ok(u) => if error.is_none() { result_vec.push(u) },
+                Err(e) => error = Some(match error {
+                    Some(acc) => acc.combine(e),
+                    None => e,
+                }),
+            }
+        }
+        match error {
+            None => Ok(result_vec),
+            Some(error) => Err(error),
+        }
+    }
+}
+
+/// Extend `Vec` with a `try_map_all` method that applies a fallible function to
+/// every element, collecting every error instead of stopping at the first one.
+///
+/// This is the error-accumulating counterpart of `try_map`, and saves
+/// materializing a `Vec<Result<U>>` just to call `flip_all` on it.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Vec`'s value type
+/// - `U`: The output `Vec`'s value type
+/// - `E`: The possible error during the mapping
+pub trait FallibleMapAllExt<T, U, E> {
+
+    /// Apply a fallible map function to every element, returning all values or all errors
+    fn try_map_all<F>(self, f: F) -> Result<Vec<U>, Vec<E>> where
+        F: FnMut(T) -> Result<U, E>;
+
+    /// Like `try_map_all`, but collects the errors into a container of the caller's choice
+    fn try_map_all_collect<C, F>(self, f: F) -> Result<Vec<U>, C> where
+        C: Default + Extend<E>,
+        F: FnMut(T) -> Result<U, E>;
+
+    /// Like `try_map_all`, but merges the errors together with `Combine`
+    fn try_map_combine<F>(self, f: F) -> Result<Vec<U>, E> where
+        E: Combine,
+        F: FnMut(T) -> Result<U, E>;
+
+}
+
+impl<T, U, E> FallibleMapAllExt<T, U, E> for Vec<T> {
+    fn try_map_all<F>(self, f: F) -> Result<Vec<U>, Vec<E>> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        self.try_map_all_collect(f)
+    }
+
+    fn try_map_all_collect<C, F>(self, mut f: F) -> Result<Vec<U>, C> where
+        C: Default + Extend<E>,
+        F: FnMut(T) -> Result<U, E>
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        let mut errors = None;
+        for t in self {
+            match f(t) {
+                Ok(u) => if errors.is_none() { result_vec.push(u) },
+                Err(e) => errors.get_or_insert_with(C::default).extend(Some(e)),
+            }
+        }
+        match errors {
+            None => Ok(result_vec),
+            Some(errors) => Err(errors),
+        }
+    }
+
+    fn try_map_combine<F>(self, mut f: F) -> Result<Vec<U>, E> where
+        E: Combine,
+        F: FnMut(T) -> Result<U, E>
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        let mut error: Option<E> = None;
+        for t in self {
+            match f(t) {
+                Ok(u) => if error.is_none() { result_vec.push(u) },
+                Err(e) => error = Some(match error {
+                    Some(acc) => acc.combine(e),
+                    None => e,
+                }),
+            }
+        }
+        match error {
+            None => Ok(result_vec),
+            Some(error) => Err(error),
+        }
+    }
+}
+
+/// Extend `Vec<Result<T>>`, and any other iterable of `Result`s, with a
+/// `partition_flip` method that splits the successes and the failures.
+///
+/// This is useful for "process what you can, report what you can't" pipelines
+/// that need both halves instead of a single `Result`.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait PartitionFlipExt<T, E> {
+
+    /// Split the items into the values and the errors, in a single pass
+    fn partition_flip(self) -> (Vec<T>, Vec<E>);
+
+    /// Like `partition_flip`, but collects the errors into a container of the caller's choice
+    fn partition_flip_collect<C>(self) -> (Vec<T>, C) where
+        C: Default + Extend<E>;
+
+}
+
+impl<I, T, E> PartitionFlipExt<T, E> for I where
+    I: IntoIterator<Item = Result<T, E>>
+{
+    fn partition_flip(self) -> (Vec<T>, Vec<E>)
+    {
+        self.partition_flip_collect()
+    }
+
+    fn partition_flip_collect<C>(self) -> (Vec<T>, C) where
+        C: Default + Extend<E>
+    {
+        let iter = self.into_iter();
+        let mut result_vec = Vec::with_capacity(iter.size_hint().0);
+        let mut errors = C::default();
+        for t in iter {
+            match t {
+                Ok(u) => result_vec.push(u),
+                Err(e) => errors.extend(Some(e)),
+            }
+        }
+        (result_vec, errors)
+    }
+}
+
+/// Extend `Vec<Result<T>>`, and any other iterable of `Result`s, with a
+/// `flip_lossy` method that keeps the successes and the positions of the failures.
+///
+/// Unlike `partition_flip`, each error is paired with the index of the element
+/// it originated from, so the partial results can be used while the failures
+/// are logged along with where they occurred.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait FlipLossyExt<T, E> {
+
+    /// Split the items into the values and the indexed errors, in a single pass
+    fn flip_lossy(self) -> (Vec<T>, Vec<(usize, E)>);
+
+    /// Like `flip_lossy`, but collects the indexed errors into a container of the caller's choice
+    fn flip_lossy_collect<C>(self) -> (Vec<T>, C) where
+        C: Default + Extend<(usize, E)>;
+
+}
+
+impl<I, T, E> FlipLossyExt<T, E> for I where
+    I: IntoIterator<Item = Result<T, E>>
+{
+    fn flip_lossy(self) -> (Vec<T>, Vec<(usize, E)>)
+    {
+        self.flip_lossy_collect()
+    }
+
+    fn flip_lossy_collect<C>(self) -> (Vec<T>, C) where
+        C: Default + Extend<(usize, E)>
+    {
+        let iter = self.into_iter();
+        let mut result_vec = Vec::with_capacity(iter.size_hint().0);
+        let mut errors = C::default();
+        for (i, t) in iter.enumerate() {
+            match t {
+                Ok(u) => result_vec.push(u),
+                Err(e) => errors.extend(Some((i, e))),
+            }
+        }
+        (result_vec, errors)
+    }
+}
+
+/// Extend `Vec<Result<T>>`, and any other iterable of `Result`s, with a `flip_ok`
+/// method that keeps only the successes.
+///
+/// `flip_ok_with` passes every discarded error to a sink closure, e.g. for
+/// logging them, which `filter_map(Result::ok)` doesn't allow.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait FlipOkExt<T, E> {
+
+    /// Keep the values and pass the errors to `sink`, in a single pass
+    fn flip_ok_with<F>(self, sink: F) -> Vec<T> where
+        F: FnMut(E);
+
+    /// Keep the values and discard the errors
+    fn flip_ok(self) -> Vec<T> where
+        Self: Sized
+    {
+        self.flip_ok_with(|_| ())
+    }
+
+}
+
+impl<I, T, E> FlipOkExt<T, E> for I where
+    I: IntoIterator<Item = Result<T, E>>
+{
+    fn flip_ok_with<F>(self, mut sink: F) -> Vec<T> where
+        F: FnMut(E)
+    {
+        let iter = self.into_iter();
+        let mut result_vec = Vec::with_capacity(iter.size_hint().0);
+        for t in iter {
+            match t {
+                Ok(u) => result_vec.push(u),
+                Err(e) => sink(e),
+            }
+        }
+        result_vec
+    }
+}
+
+/// Extend `Vec<Result<T>>`, and any other iterable of `Result`s, with an
+/// `into_errors` method that keeps only the failures.
+///
+/// This is the counterpart of `flip_ok`, for error-reporting paths that have no
+/// use for the values.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait IntoErrorsExt<T, E> {
+
+    /// Keep the errors and discard the values, in a single pass
+    fn into_errors(self) -> Vec<E>;
+
+}
+
+impl<I, T, E> IntoErrorsExt<T, E> for I where
+    I: IntoIterator<Item = Result<T, E>>
+{
+    fn into_errors(self) -> Vec<E>
+    {
+        self.into_iter().filter_map(Result::err).collect()
+    }
+}
+
+/// Extend `Vec<Result<T>>`, and any other iterable of `Result`s, with a
+/// `first_ok` method that returns the first success, or all the errors if every
+/// item failed.
+///
+/// This is the "try the mirrors in order until one works" pattern, the dual of
+/// `flip_all`. The items after the first success aren't consumed, so for a lazy
+/// iterator the remaining attempts aren't even made. An empty input yields an
+/// empty list of errors.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait FirstOkExt<T, E> {
+
+    /// Return the first value, or all the errors if there's none
+    fn first_ok(self) -> Result<T, Vec<E>>;
+
+}
+
+impl<I, T, E> FirstOkExt<T, E> for I where
+    I: IntoIterator<Item = Result<T, E>>
+{
+    fn first_ok(self) -> Result<T, Vec<E>>
+    {
+        let mut errors = Vec::new();
+        for t in self {
+            match t {
+                Ok(u) => return Ok(u),
+                Err(e) => errors.push(e),
+            }
+        }
+        Err(errors)
+    }
+}
+
+/// Extend `Vec<FnOnce() -> Result<Option<T>>>`, and any other iterable of
+/// fallible producers, with a `try_or_else_chain` method that tries them in order
+/// until one yields a value.
+///
+/// This is the shape of configuration resolution: command-line flag, then
+/// environment variable, then file, then default service. A producer returns
+/// `Ok(None)` when it has nothing to offer, and an error when it failed, e.g.
+/// on a malformed value; in both cases, the next producer is tried. The result
+/// contains the value, if any producer yielded one, along with the errors of
+/// the failed attempts before it. The producers after the one that yielded a
+/// value aren't called.
+///
+/// Producers of different types can be chained by boxing them.
+///
+/// # Type parameters
+///
+/// - `T`: The type of the produced value
+/// - `E`: The error type of the producers
+pub trait TryOrElseChainExt<T, E> {
+
+    /// Call the producers in order until one yields a value, collecting the errors
+    fn try_or_else_chain(self) -> (Option<T>, Vec<E>);
+
+}
+
+impl<I, F, T, E> TryOrElseChainExt<T, E> for I where
+    I: IntoIterator<Item = F>,
+    F: FnOnce() -> Result<Option<T>, E>
+{
+    fn try_or_else_chain(self) -> (Option<T>, Vec<E>)
+    {
+        let mut errors = Vec::new();
+        for f in self {
+            match f() {
+                Ok(Some(t)) => return (Some(t), errors),
+                Ok(None) => (),
+                Err(e) => errors.push(e),
+            }
+        }
+        (None, errors)
+    }
+}
+
+/// Extend `Vec<Result<T>>` and `Vec<Option<T>>` with methods that substitute a fallback value for the failed elements.
+///
+/// This is the "degrade, don't die" mode of `flip`: the output always has one
+/// value per input element. For `Option`s, the error passed to the fallback is `()`.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait FlipOrDefaultExt<T, E> {
+
+    /// Replace every failed element with the result of calling `f` with its error
+    fn flip_or_else<F>(self, f: F) -> Vec<T> where
+        F: FnMut(E) -> T;
+
+    /// Replace every failed element with `T::default()`
+    fn flip_or_default(self) -> Vec<T> where
+        T: Default,
+        Self: Sized
+    {
+        self.flip_or_else(|_| T::default())
+    }
+
+    /// Replace every failed element with `T::default()`, and return the errors as well
+    fn flip_or_default_with_errors(self) -> (Vec<T>, Vec<E>) where
+        T: Default,
+        Self: Sized
+    {
+        let mut errors = Vec::new();
+        let result_vec = self.flip_or_else(|e| {
+            errors.push(e);
+            T::default()
+        });
+        (result_vec, errors)
+    }
+
+}
+
+impl<T, E> FlipOrDefaultExt<T, E> for Vec<Result<T, E>> {
+    fn flip_or_else<F>(self, mut f: F) -> Vec<T> where
+        F: FnMut(E) -> T
+    {
+        self.into_iter().map(|r| match r {
+            Ok(t) => t,
+            Err(e) => f(e),
+        }).collect()
+    }
+}
+
+impl<T> FlipOrDefaultExt<T, ()> for Vec<Option<T>> {
+    fn flip_or_else<F>(self, mut f: F) -> Vec<T> where
+        F: FnMut(()) -> T
+    {
+        self.into_iter().map(|o| o.unwrap_or_else(|| f(()))).collect()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use FallibleMapAllExt;
+    use FirstOkExt;
+    use FlipAllExt;
+    use FlipOrDefaultExt;
+    use FlipLossyExt;
+    use FlipOkExt;
+    use IntoErrorsExt;
+    use PartitionFlipExt;
+    use TryOrElseChainExt;
+
+    #[test]
+    fn test_flip_all_1() {
+        let x: Vec<Result<i32, &'static str>> = vec![Ok(1), Ok(2), Ok(3)];
+        assert_eq!(x.flip_all(), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_flip_all_2() {
+        let x = vec![Ok(1), Err("oh noes"), Ok(3), Err("oh foes")];
+        assert_eq!(x.flip_all(), Err(vec!["oh noes", "oh foes"]));
+    }
+
+    #[test]
+    fn test_flip_all_collect() {
+        let x = vec![Ok(1), Err("oh noes"), Ok(3), Err("oh noes"), Err("oh foes")];
+        let errors: BTreeSet<_> = x.flip_all_collect().unwrap_err();
+        assert_eq!(errors.into_iter().collect::<Vec<_>>(), vec!["oh foes", "oh noes"]);
+    }
+
+    #[test]
+    fn test_flip_combine() {
+        let x = vec![Ok(1), Err(()), Ok(3), Err(())];
+        assert_eq!(x.flip_combine(), Err(()));
+        let x: Vec<Result<i32, usize>> = vec![Ok(1), Ok(3)];
+        assert_eq!(x.flip_combine(), Ok(vec![1, 3]));
+    }
+
+    #[test]
+    fn test_try_map_all_1() {
+        let x = vec!["1", "2", "3"].try_map_all(|s| s.parse::<i32>());
+        assert_eq!(x, Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_try_map_all_2() {
+        let x = vec![1, -2, 3, -4].try_map_all(|x| if x > 0 { Ok(x) } else { Err(x) });
+        assert_eq!(x, Err(vec![-2, -4]));
+    }
+
+    #[test]
+    fn test_try_map_all_collect() {
+        let x: Result<Vec<i32>, String> = vec!["1", "a", "b"].try_map_all_collect(|s| s.parse().map_err(|_| s));
+        assert_eq!(x, Err("ab".to_string()));
+    }
+
+    #[test]
+    fn test_try_map_combine() {
+        let x = vec!["1", "a", "2", "b"].try_map_combine(|s| s.parse::<i32>().map_err(|_| 1usize));
+        assert_eq!(x, Err(2));
+    }
+
+    #[test]
+    fn test_partition_flip_vec() {
+        let x = vec![Ok(1), Err("oh noes"), Ok(3), Err("oh foes")];
+        assert_eq!(x.partition_flip(), (vec![1, 3], vec!["oh noes", "oh foes"]));
+        let x = vec![Ok(1), Err("oh noes"), Err("oh noes")];
+        assert_eq!(x.partition_flip_collect::<BTreeSet<_>>(), (vec![1], Some("oh noes").into_iter().collect()));
+    }
+
+    #[test]
+    fn test_partition_flip_iter() {
+        let (ok, err) = vec!["1", "x", "3"].into_iter().map(|s| s.parse::<i32>()).partition_flip();
+        assert_eq!(ok, vec![1, 3]);
+        assert_eq!(err.len(), 1);
+    }
+
+    #[test]
+    fn test_flip_lossy() {
+        let x = vec![Ok(1), Err("oh noes"), Ok(3), Err("oh foes")];
+        assert_eq!(x.flip_lossy(), (vec![1, 3], vec![(1, "oh noes"), (3, "oh foes")]));
+        let x = vec![Ok(1), Err("oh noes"), Err("oh noes")];
+        let (ok, err): (_, BTreeMap<usize, &str>) = x.flip_lossy_collect();
+        assert_eq!((ok, err.keys().copied().collect::<Vec<_>>()), (vec![1], vec![1, 2]));
+    }
+
+    #[test]
+    fn test_flip_or_default() {
+        let x = vec![Ok(1), Err("oh noes"), Ok(3)];
+        assert_eq!(x.flip_or_default(), vec![1, 0, 3]);
+        let x = vec![Ok(1), Err("oh noes"), Ok(3), Err("oh foes")];
+        assert_eq!(x.flip_or_default_with_errors(), (vec![1, 0, 3, 0], vec!["oh noes", "oh foes"]));
+        assert_eq!(vec![Some(1), None].flip_or_default(), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_flip_or_else() {
+        let x = vec![Ok(1), Err("oh noes"), Ok(3)];
+        assert_eq!(x.flip_or_else(|e| e.len() as i32), vec![1, 7, 3]);
+        assert_eq!(vec![None, Some(2)].flip_or_else(|()| -1), vec![-1, 2]);
+    }
+
+    #[test]
+    fn test_flip_ok() {
+        let x = vec![Ok(1), Err("oh noes"), Ok(3), Err("oh foes")];
+        assert_eq!(x.flip_ok(), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_flip_ok_with() {
+        let mut log = Vec::new();
+        let x = vec![Ok(1), Err("oh noes"), Ok(3), Err("oh foes")];
+        assert_eq!(x.flip_ok_with(|e| log.push(e)), vec![1, 3]);
+        assert_eq!(log, vec!["oh noes", "oh foes"]);
+    }
+
+    #[test]
+    fn test_first_ok() {
+        let x = vec![Err("oh noes"), Ok(2), Err("oh foes")];
+        assert_eq!(x.first_ok(), Ok(2));
+        let x: Vec<Result<i32, _>> = vec![Err("oh noes"), Err("oh foes")];
+        assert_eq!(x.first_ok(), Err(vec!["oh noes", "oh foes"]));
+        assert_eq!(Vec::<Result<i32, ()>>::new().first_ok(), Err(vec![]));
+    }
+
+    #[test]
+    fn test_first_ok_lazy() {
+        let mut tried = Vec::new();
+        let mirrors = ["a", "b", "c"].iter().map(|m| {
+            tried.push(*m);
+            if *m == "b" { Ok(m.len()) } else { Err(*m) }
+        });
+        assert_eq!(mirrors.first_ok(), Ok(1));
+        assert_eq!(tried, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_into_errors() {
+        let x = vec![Ok(1), Err("oh noes"), Ok(3), Err("oh foes")];
+        assert_eq!(x.iter().cloned().into_errors(), vec!["oh noes", "oh foes"]);
+        assert_eq!(x.into_errors(), vec!["oh noes", "oh foes"]);
+        assert_eq!(vec![Ok::<_, ()>(1)].into_errors(), vec![]);
+    }
+
+    #[test]
+    fn test_try_or_else_chain_1() {
+        type Producer<'a> = Box<dyn FnOnce() -> Result<Option<i32>, String> + 'a>;
+
+        let flag = None;
+        let env = "x";
+        let producers: Vec<Producer> = vec![
+            Box::new(|| Ok(flag)),
+            Box::new(|| env.parse().map(Some).map_err(|_| format!("invalid value: {}", env))),
+            Box::new(|| Ok(Some(8080))),
+            Box::new(|| panic!("not reached")),
+        ];
+        assert_eq!(producers.try_or_else_chain(), (Some(8080), vec!["invalid value: x".to_string()]));
+    }
+
+    #[test]
+    fn test_try_or_else_chain_2() {
+        let producers = [|| Ok(None), || Err("oh noes")];
+        assert_eq!(producers.try_or_else_chain(), (None::<i32>, vec!["oh noes"]));
+    }
+}
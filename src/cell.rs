@@ -0,0 +1,138 @@
+use core::cell::{BorrowMutError, Cell, OnceCell, RefCell};
+
+/// Extend `RefCell<Option<T>>` with a `try_map_borrowed` method that applies a
+/// fallible function to the contents in place.
+///
+/// The cell is borrowed mutably for the duration of the call. If it's already
+/// borrowed, the `BorrowMutError` is converted into the error type instead of
+/// panicking.
+///
+/// # Type parameters
+///
+/// - `T`: The type of the contents
+pub trait FallibleMapRefCellExt<T> {
+
+    /// Apply a fallible function to the contents, if any
+    fn try_map_borrowed<U, E, F>(&self, f: F) -> Result<Option<U>, E> where
+        E: From<BorrowMutError>,
+        F: FnOnce(&mut T) -> Result<U, E>;
+
+}
+
+impl<T> FallibleMapRefCellExt<T> for RefCell<Option<T>> {
+    fn try_map_borrowed<U, E, F>(&self, f: F) -> Result<Option<U>, E> where
+        E: From<BorrowMutError>,
+        F: FnOnce(&mut T) -> Result<U, E>
+    {
+        match self.try_borrow_mut()?.as_mut() {
+            Some(t) => f(t).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Extend `Cell<Option<T>>` with a `try_map_taken` method that applies a fallible
+/// function to the contents in place.
+///
+/// The contents are taken out of the cell for the duration of the call and put
+/// back afterwards, whether the function succeeds or not. If the function panics,
+/// the cell is left empty.
+///
+/// # Type parameters
+///
+/// - `T`: The type of the contents
+pub trait FallibleMapCellExt<T> {
+
+    /// Apply a fallible function to the contents, if any
+    fn try_map_taken<U, E, F>(&self, f: F) -> Result<Option<U>, E> where
+        F: FnOnce(&mut T) -> Result<U, E>;
+
+}
+
+impl<T> FallibleMapCellExt<T> for Cell<Option<T>> {
+    fn try_map_taken<U, E, F>(&self, f: F) -> Result<Option<U>, E> where
+        F: FnOnce(&mut T) -> Result<U, E>
+    {
+        let mut contents = self.take();
+        let result = match contents.as_mut() {
+            Some(t) => f(t).map(Some),
+            None => Ok(None),
+        };
+        self.set(contents);
+        result
+    }
+}
+
+/// Extend `OnceCell` and `OnceLock` with a `get_or_try_init_with` method, whose
+/// initializer is fallible.
+///
+/// This is a stable counterpart of the unstable `get_or_try_init` methods of the
+/// standard library, named differently so as not to collide with them.
+///
+/// If the initializer fails, the cell stays uninitialized and the error is
+/// returned, so a later call can try again. For `OnceLock`, several threads may
+/// run their initializers concurrently; the first one to succeed wins, and the
+/// other values are dropped.
+///
+/// # Type parameters
+///
+/// - `T`: The type of the contents
+pub trait TryOnceExt<T> {
+
+    /// Return the contents, initializing them with `f` first if the cell is uninitialized
+    fn get_or_try_init_with<E, F>(&self, f: F) -> Result<&T, E> where
+        F: FnOnce() -> Result<T, E>;
+
+}
+
+impl<T> TryOnceExt<T> for OnceCell<T> {
+    fn get_or_try_init_with<E, F>(&self, f: F) -> Result<&T, E> where
+        F: FnOnce() -> Result<T, E>
+    {
+        if let Some(t) = self.get() {
+            return Ok(t);
+        }
+        let t = f()?;
+        Ok(self.get_or_init(|| t))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::cell::{Cell, OnceCell, RefCell};
+
+    use test_util::{try_increment, StateError};
+    use {FallibleMapCellExt, FallibleMapRefCellExt, TryOnceExt};
+
+    #[test]
+    fn test_try_map_borrowed() {
+        let state = RefCell::new(Some(1u8));
+        assert_eq!(state.try_map_borrowed(try_increment), Ok(Some(2)));
+        {
+            let _guard = state.borrow();
+            assert_eq!(state.try_map_borrowed(try_increment), Err(StateError::Busy));
+        }
+        *state.borrow_mut() = Some(255);
+        assert_eq!(state.try_map_borrowed(try_increment), Err(StateError::Overflow));
+        *state.borrow_mut() = None;
+        assert_eq!(state.try_map_borrowed(try_increment), Ok(None));
+    }
+
+    #[test]
+    fn test_try_map_taken() {
+        let state = Cell::new(Some(1u8));
+        assert_eq!(state.try_map_taken(try_increment), Ok(Some(2)));
+        state.set(Some(255));
+        assert_eq!(state.try_map_taken(try_increment), Err(StateError::Overflow));
+        assert_eq!(state.get(), Some(255));
+    }
+
+    #[test]
+    fn test_get_or_try_init_once_cell() {
+        let cell = OnceCell::new();
+        assert_eq!(cell.get_or_try_init_with(|| "x".parse::<i32>()).map_err(|_| ()), Err(()));
+        assert_eq!(cell.get(), None);
+        assert_eq!(cell.get_or_try_init_with(|| "42".parse::<i32>()), Ok(&42));
+        assert_eq!(cell.get_or_try_init_with(|| "x".parse::<i32>()), Ok(&42));
+    }
+}
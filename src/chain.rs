@@ -0,0 +1,151 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use policy::{AccumulateAll, ErrorPolicy, FailFast, KeepPartial};
+use {FlipWithPolicyExt, PartitionFlipExt};
+
+/// A chain of fallible map steps, recorded now and executed later
+///
+/// The steps are added with `then` and run on every element of an input with
+/// `run`, which takes an `ErrorPolicy`. The same chain definition can thus serve
+/// both a strict mode, with `FailFast`, and a diagnostic mode that reports every
+/// failure, with `AccumulateAll` or `KeepPartial`. Unlike the `pipeline!` macro,
+/// each element goes through all the steps before the next element is mapped.
+///
+/// ```
+/// use try_map::TryChain;
+/// use try_map::policy::{AccumulateAll, FailFast};
+/// use try_map::prelude::*;
+///
+/// let mut chain = TryChain::new()
+///     .then(|s: &str| s.parse::<u8>().map_err(|_| format!("{} is not a number", s)))
+///     .then(|x| x.checked_mul(2).ok_or(format!("{} is too big", x)));
+/// assert_eq!(chain.run(vec!["1", "x", "200"], FailFast), Err("x is not a number".to_string()));
+/// assert_eq!(chain.run(vec!["1", "x", "200"], AccumulateAll).unwrap_err().len(), 2);
+/// assert_eq!(chain.run(Some("2"), FailFast), Ok(Some(4)));
+/// ```
+///
+/// # Type parameters
+///
+/// - `T`: The input value type
+/// - `U`: The output value type of the last step
+/// - `E`: The error type shared by the steps
+pub struct TryChain<'a, T, U, E> {
+    f: Box<dyn FnMut(T) -> Result<U, E> + 'a>,
+}
+
+impl<'a, T: 'a, E: 'a> TryChain<'a, T, T, E> {
+
+    /// Construct an empty chain, which passes the values through unchanged
+    pub fn new() -> Self {
+        TryChain { f: Box::new(Ok) }
+    }
+}
+
+impl<'a, T: 'a, E: 'a> Default for TryChain<'a, T, T, E> {
+    fn default() -> Self {
+        TryChain::new()
+    }
+}
+
+impl<'a, T: 'a, U: 'a, E: 'a> TryChain<'a, T, U, E> {
+
+    /// Append a fallible map step to the chain
+    pub fn then<V, G>(self, mut g: G) -> TryChain<'a, T, V, E> where
+        G: FnMut(U) -> Result<V, E> + 'a
+    {
+        let mut f = self.f;
+        TryChain { f: Box::new(move |t| f(t).and_then(&mut g)) }
+    }
+}
+
+/// Run a `TryChain` over an input container according to an `ErrorPolicy`
+///
+/// Implemented for `Vec<T>` and `Option<T>` inputs. The return types are those
+/// of `flip_with_policy`: `FailFast` stops at the first error, while
+/// `AccumulateAll` and `KeepPartial` run the chain on every element.
+///
+/// # Type parameters
+///
+/// - `C`: The input container
+/// - `P`: The error policy
+pub trait TryChainRun<C, P: ErrorPolicy> {
+    type Output;
+
+    fn run(&mut self, input: C, policy: P) -> Self::Output;
+}
+
+impl<'a, T, U, E> TryChainRun<Vec<T>, FailFast> for TryChain<'a, T, U, E> {
+    type Output = Result<Vec<U>, E>;
+
+    fn run(&mut self, input: Vec<T>, _: FailFast) -> Result<Vec<U>, E> {
+        input.into_iter().map(&mut self.f).collect()
+    }
+}
+
+impl<'a, T, U, E> TryChainRun<Vec<T>, AccumulateAll> for TryChain<'a, T, U, E> {
+    type Output = Result<Vec<U>, Vec<E>>;
+
+    fn run(&mut self, input: Vec<T>, policy: AccumulateAll) -> Result<Vec<U>, Vec<E>> {
+        input.into_iter().map(&mut self.f).collect::<Vec<_>>().flip_with_policy(policy)
+    }
+}
+
+impl<'a, T, U, E> TryChainRun<Vec<T>, KeepPartial> for TryChain<'a, T, U, E> {
+    type Output = (Vec<U>, Vec<E>);
+
+    fn run(&mut self, input: Vec<T>, _: KeepPartial) -> (Vec<U>, Vec<E>) {
+        input.into_iter().map(&mut self.f).partition_flip()
+    }
+}
+
+impl<'a, T, U, E, P: ErrorPolicy> TryChainRun<Option<T>, P> for TryChain<'a, T, U, E> where
+    Option<Result<U, E>>: FlipWithPolicyExt<P>
+{
+    type Output = <Option<Result<U, E>> as FlipWithPolicyExt<P>>::Output;
+
+    fn run(&mut self, input: Option<T>, policy: P) -> Self::Output {
+        input.map(&mut self.f).flip_with_policy(policy)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use policy::{AccumulateAll, FailFast, KeepPartial};
+    use {TryChain, TryChainRun};
+
+    fn chain<'a>() -> TryChain<'a, i32, i32, String> {
+        TryChain::new()
+            .then(|x: i32| if x > 0 { Ok(x) } else { Err(format!("{} is not positive", x)) })
+            .then(|x| x.checked_mul(1000).ok_or(format!("{} is too big", x)))
+    }
+
+    #[test]
+    fn test_try_chain_vec() {
+        let input = || vec![1, -2, 3_000_000, 4];
+        assert_eq!(chain().run(input(), FailFast), Err("-2 is not positive".to_string()));
+        assert_eq!(chain().run(input(), AccumulateAll), Err(vec!["-2 is not positive".to_string(), "3000000 is too big".to_string()]));
+        assert_eq!(chain().run(input(), KeepPartial).0, vec![1000, 4000]);
+        assert_eq!(chain().run(vec![1, 2], FailFast), Ok(vec![1000, 2000]));
+    }
+
+    #[test]
+    fn test_try_chain_fail_fast_short_circuits() {
+        let mut calls = 0;
+        {
+            let mut chain = TryChain::new().then(|x: i32| {
+                calls += 1;
+                if x > 0 { Ok(x) } else { Err(x) }
+            });
+            assert_eq!(chain.run(vec![1, -2, 3], FailFast), Err(-2));
+        }
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_try_chain_option() {
+        assert_eq!(chain().run(Some(2), FailFast), Ok(Some(2000)));
+        assert_eq!(chain().run(Some(-2), AccumulateAll), Err(vec!["-2 is not positive".to_string()]));
+        assert_eq!(chain().run(None, KeepPartial), (None, vec![]));
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A type whose values can be merged together, used for accumulating errors
+///
+/// This is a semigroup: `combine` must be associative. Accumulating APIs such as
+/// `flip_combine` and `try_map_combine` merge the errors through this trait
+/// instead of always building a `Vec`, which allows e.g. counting the errors
+/// (`usize`) or concatenating error messages (`String`) without extra allocations.
+pub trait Combine {
+
+    /// Merge `other` into `self`
+    fn combine(self, other: Self) -> Self;
+
+}
+
+impl Combine for () {
+    fn combine(self, _: ()) {}
+}
+
+/// Addition, so that errors mapped to `1` get counted
+///
+/// The count saturates at `usize::MAX` instead of overflowing.
+impl Combine for usize {
+    fn combine(self, other: usize) -> usize {
+        self.saturating_add(other)
+    }
+}
+
+/// Concatenation
+#[cfg(feature = "alloc")]
+impl Combine for String {
+    fn combine(mut self, other: String) -> String {
+        self.push_str(&other);
+        self
+    }
+}
+
+/// Concatenation
+#[cfg(feature = "alloc")]
+impl<T> Combine for Vec<T> {
+    fn combine(mut self, other: Vec<T>) -> Vec<T> {
+        self.extend(other);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Combine;
+
+    #[test]
+    fn test_combine() {
+        assert_eq!(1usize.combine(2), 3);
+        assert_eq!(usize::MAX.combine(1), usize::MAX);
+        assert_eq!(().combine(()), ());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_combine_alloc() {
+        assert_eq!("oh ".to_string().combine("noes".to_string()), "oh noes");
+        assert_eq!(vec![1].combine(vec![2, 3]), vec![1, 2, 3]);
+    }
+}
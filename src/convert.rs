@@ -0,0 +1,83 @@
+use Flippable;
+
+/// Extend every container that flips into a `Result` with `flip_map_err` and
+/// `flip_into` methods that convert the error type while flipping.
+///
+/// These fuse the common `.flip().map_err(...)` and `.flip().map_err(Into::into)`
+/// chains. They are implemented for every `Flippable` whose output is a `Result`,
+/// so they are available for `Option<Result<T, E>>`, `Vec<Result<T, E>>` and any
+/// downstream container that implements `Flippable`.
+pub trait FlipMapErrExt {
+    /// The flipped container, e.g. `Vec<T>` for `Vec<Result<T, E>>`
+    type Ok;
+    /// The original error type
+    type Error;
+
+    /// Flip, converting the error with `f`
+    fn flip_map_err<E2, F>(self, f: F) -> Result<Self::Ok, E2> where
+        F: FnOnce(Self::Error) -> E2;
+
+    /// Flip, converting the error with `From`
+    fn flip_into<E2>(self) -> Result<Self::Ok, E2> where
+        E2: From<Self::Error>,
+        Self: Sized
+    {
+        self.flip_map_err(E2::from)
+    }
+}
+
+impl<C, T, E> FlipMapErrExt for C where
+    C: Flippable<Output = Result<T, E>>
+{
+    type Ok = T;
+    type Error = E;
+
+    fn flip_map_err<E2, F>(self, f: F) -> Result<T, E2> where
+        F: FnOnce(E) -> E2
+    {
+        self.flip().map_err(f)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use FlipMapErrExt;
+
+    #[derive(Debug, PartialEq)]
+    struct MyError(&'static str);
+
+    impl From<&'static str> for MyError {
+        fn from(s: &'static str) -> MyError {
+            MyError(s)
+        }
+    }
+
+    #[test]
+    fn test_flip_map_err_option() {
+        assert_eq!(Some(Err::<i32, _>("oh noes")).flip_map_err(|e| e.len()), Err(7));
+        assert_eq!(Some(Ok::<_, &'static str>(42)).flip_into::<MyError>(), Ok(Some(42)));
+        assert_eq!(None::<Result<i32, &'static str>>.flip_map_err(|e| e.len()), Ok(None));
+    }
+
+    #[test]
+    fn test_flip_into_vec() {
+        fn inner() -> Result<Vec<i32>, MyError> {
+            vec![Ok(1), Err("oh noes"), Err("oh foes")].flip_into()
+        }
+        assert_eq!(inner(), Err(MyError("oh noes")));
+        assert_eq!(vec![Ok::<_, &'static str>(1), Ok(2)].flip_into::<MyError>(), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_flip_into_core_containers() {
+        use std::ops::ControlFlow;
+        use std::task::Poll;
+
+        assert_eq!([Ok(1), Err("oh noes")].flip_into::<MyError>(), Err(MyError("oh noes")));
+        assert_eq!(Poll::Ready(Err::<i32, _>("oh noes")).flip_into::<MyError>(), Err(MyError("oh noes")));
+        let x: ControlFlow<(), Result<i32, &'static str>> = ControlFlow::Continue(Ok(1));
+        assert_eq!(x.flip_into::<MyError>(), Ok(ControlFlow::Continue(1)));
+        let x: Vec<Result<i32, &'static str>> = vec![Ok(1), Ok(2)];
+        assert_eq!(x[..].flip_into::<MyError>(), Ok(vec![1, 2]));
+    }
+}
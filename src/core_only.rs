@@ -0,0 +1,110 @@
+//! The subset of the crate that only depends on `core`
+//!
+//! Everything reachable from this module works without `std` and without an
+//! allocator, regardless of the enabled features, so kernel and firmware code can
+//! import from here and be sure not to pull in `alloc` by accident.
+//!
+//! Besides `Option`, `flip` is implemented here for fixed-size arrays, `Poll`
+//! and `ControlFlow`.
+
+use core::ops::ControlFlow;
+use core::task::Poll;
+
+pub use {Combine, FallibleMapBreakExt, FallibleMapCellExt, FallibleMapExt, FallibleMapFullExt, FallibleMapMutExt, FallibleMapOptExt, FallibleMapRefCellExt, FallibleZipExt, FlipContextExt, FlipFlattenExt, FlipMapErrExt, FlipZipExt, Flippable, Sequence, Traverse, TryBinarySearchExt, TryDefault, TryDefaultExt, TryExtend, TryFunctor, TryOnceExt, TryParseExt, TryUpdateExt};
+#[cfg(feature = "async")]
+pub use {FallibleFilterAsyncExt, FallibleMapAsyncExt, FutureFlipExt};
+
+impl<T, E, const N: usize> Flippable for [Result<T, E>; N] {
+    type Item = T;
+    type Output = Result<[T; N], E>;
+
+    fn flip(self) -> Result<[T; N], E>
+    {
+        let mut error = None;
+        let values = self.map(|r| match r {
+            Ok(t) => Some(t),
+            Err(e) => {
+                if error.is_none() {
+                    error = Some(e);
+                }
+                None
+            },
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(values.map(Option::unwrap)),
+        }
+    }
+}
+
+impl<T, const N: usize> Flippable for [Option<T>; N] {
+    type Item = T;
+    type Output = Option<[T; N]>;
+
+    fn flip(self) -> Option<[T; N]>
+    {
+        if self.iter().any(Option::is_none) {
+            return None;
+        }
+        Some(self.map(Option::unwrap))
+    }
+}
+
+impl<T, E> Flippable for Poll<Result<T, E>> {
+    type Item = T;
+    type Output = Result<Poll<T>, E>;
+
+    fn flip(self) -> Result<Poll<T>, E>
+    {
+        match self {
+            Poll::Ready(r) => r.map(Poll::Ready),
+            Poll::Pending => Ok(Poll::Pending),
+        }
+    }
+}
+
+impl<B, T, E> Flippable for ControlFlow<B, Result<T, E>> {
+    type Item = T;
+    type Output = Result<ControlFlow<B, T>, E>;
+
+    fn flip(self) -> Result<ControlFlow<B, T>, E>
+    {
+        match self {
+            ControlFlow::Continue(r) => r.map(ControlFlow::Continue),
+            ControlFlow::Break(b) => Ok(ControlFlow::Break(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ops::ControlFlow;
+    use core::task::Poll;
+
+    use super::Flippable;
+
+    #[test]
+    fn test_flip_array() {
+        assert_eq!([Ok(1), Ok(2), Ok(3)].flip(), Ok::<_, &'static str>([1, 2, 3]));
+        assert_eq!([Ok(1), Err("oh noes"), Err("oh foes")].flip(), Err("oh noes"));
+        assert_eq!([Some(1), Some(2)].flip(), Some([1, 2]));
+        assert_eq!([Some(1), None].flip(), None);
+    }
+
+    #[test]
+    fn test_flip_poll() {
+        assert_eq!(Poll::Ready(Ok::<_, ()>(42)).flip(), Ok(Poll::Ready(42)));
+        assert_eq!(Poll::Ready(Err::<i32, _>("oh noes")).flip(), Err("oh noes"));
+        assert_eq!(Poll::Pending::<Result<i32, ()>>.flip(), Ok(Poll::Pending));
+    }
+
+    #[test]
+    fn test_flip_control_flow() {
+        let x: ControlFlow<&'static str, Result<i32, ()>> = ControlFlow::Continue(Ok(42));
+        assert_eq!(x.flip(), Ok(ControlFlow::Continue(42)));
+        let x: ControlFlow<&'static str, Result<i32, ()>> = ControlFlow::Break("done");
+        assert_eq!(x.flip(), Ok(ControlFlow::Break("done")));
+        let x: ControlFlow<&'static str, Result<i32, &'static str>> = ControlFlow::Continue(Err("oh noes"));
+        assert_eq!(x.flip(), Err("oh noes"));
+    }
+}
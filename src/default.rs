@@ -0,0 +1,73 @@
+/// A type with a default value whose construction may fail
+///
+/// This is the fallible counterpart of `Default`, for defaults that come from
+/// configuration files, environment variables or probing the system.
+pub trait TryDefault: Sized {
+    /// The error that may occur while constructing the default value
+    type Error;
+
+    /// Try to construct the default value
+    fn try_default() -> Result<Self, Self::Error>;
+}
+
+/// Extend `Option` with a `try_unwrap_or_try_default` method, the fallible
+/// counterpart of `unwrap_or_default`.
+///
+/// The default value is only constructed if the option is empty.
+///
+/// # Type parameters
+///
+/// - `T`: The `Option`'s value type
+pub trait TryDefaultExt<T: TryDefault> {
+
+    /// Return the contained value, or try to construct the default value if there's none
+    fn try_unwrap_or_try_default(self) -> Result<T, T::Error>;
+
+}
+
+impl<T: TryDefault> TryDefaultExt<T> for Option<T> {
+    fn try_unwrap_or_try_default(self) -> Result<T, T::Error> {
+        match self {
+            Some(t) => Ok(t),
+            None => T::try_default(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use {TryDefault, TryDefaultExt};
+
+    #[derive(Debug, PartialEq)]
+    struct Port(u16);
+
+    impl TryDefault for Port {
+        type Error = ::std::num::ParseIntError;
+
+        fn try_default() -> Result<Port, Self::Error> {
+            "8080".parse().map(Port)
+        }
+    }
+
+    #[test]
+    fn test_try_unwrap_or_try_default() {
+        assert_eq!(Some(Port(80)).try_unwrap_or_try_default(), Ok(Port(80)));
+        assert_eq!(None.try_unwrap_or_try_default(), Ok(Port(8080)));
+    }
+
+    #[test]
+    fn test_try_unwrap_or_try_default_error() {
+        struct Broken;
+
+        impl TryDefault for Broken {
+            type Error = &'static str;
+
+            fn try_default() -> Result<Broken, &'static str> {
+                Err("oh noes")
+            }
+        }
+
+        assert!(None::<Broken>.try_unwrap_or_try_default().is_err());
+        assert!(Some(Broken).try_unwrap_or_try_default().is_ok());
+    }
+}
@@ -0,0 +1,227 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+
+/// An error of a fallible map, along with the input that caused it
+///
+/// Returned by the `try_map_full` methods, so that the offending value can be
+/// reported, retried or sent to a dead-letter queue instead of being dropped.
+///
+/// # Type parameters
+///
+/// - `T`: The input type
+/// - `E`: The error type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TryMapError<T, E> {
+    /// The input for which the mapping failed
+    pub input: T,
+    /// The error returned by the mapping
+    pub error: E,
+}
+
+/// Displays the error
+impl<T, E: fmt::Display> fmt::Display for TryMapError<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug, E: std::error::Error> std::error::Error for TryMapError<T, E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// An error of a fallible element-wise combination of two collections
+///
+/// Returned by `try_zip_map`, which checks the lengths before combining anything.
+///
+/// # Type parameters
+///
+/// - `E`: The error type of the combining function
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ZipMapError<E> {
+    /// The collections have different lengths
+    LengthMismatch {
+        /// The length of the left-hand collection
+        left: usize,
+        /// The length of the right-hand collection
+        right: usize,
+    },
+    /// The combining function failed
+    Map(E),
+}
+
+/// Displays the lengths, or the error of the combining function
+impl<E: fmt::Display> fmt::Display for ZipMapError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ZipMapError::LengthMismatch { left, right } => write!(f, "length mismatch: {} and {} elements", left, right),
+            ZipMapError::Map(ref e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error> std::error::Error for ZipMapError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            ZipMapError::LengthMismatch { .. } => None,
+            ZipMapError::Map(ref e) => e.source(),
+        }
+    }
+}
+
+/// An error of a fallible combination of two maps by key
+///
+/// Returned by `try_zip_map_by_key` with `UnmatchedKeys::Error`.
+///
+/// # Type parameters
+///
+/// - `K`: The key type
+/// - `E`: The error type of the combining function
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ZipByKeyError<K, E> {
+    /// The key is only present in the left-hand map
+    OnlyInLeft(K),
+    /// The key is only present in the right-hand map
+    OnlyInRight(K),
+    /// The combining function failed
+    Map(E),
+}
+
+/// Displays the unmatched key, or the error of the combining function
+impl<K: fmt::Debug, E: fmt::Display> fmt::Display for ZipByKeyError<K, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ZipByKeyError::OnlyInLeft(ref k) => write!(f, "key {:?} is only present in the left-hand map", k),
+            ZipByKeyError::OnlyInRight(ref k) => write!(f, "key {:?} is only present in the right-hand map", k),
+            ZipByKeyError::Map(ref e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: fmt::Debug, E: std::error::Error> std::error::Error for ZipByKeyError<K, E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            ZipByKeyError::Map(ref e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+/// An error of an order-checked fallible key mapping
+///
+/// Returned by `try_map_keys_checked`, with the converted key at which the
+/// problem was detected.
+///
+/// # Type parameters
+///
+/// - `K`: The converted key type
+/// - `E`: The error type of the key mapping
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKeysError<K, E> {
+    /// Two keys were converted into the same key
+    Duplicate(K),
+    /// A key was converted into a key ordered before that of the previous key
+    OutOfOrder(K),
+    /// The key mapping failed
+    Map(E),
+}
+
+/// Displays the offending key, or the error of the key mapping
+impl<K: fmt::Debug, E: fmt::Display> fmt::Display for MapKeysError<K, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MapKeysError::Duplicate(ref k) => write!(f, "duplicate key {:?}", k),
+            MapKeysError::OutOfOrder(ref k) => write!(f, "key {:?} is out of order", k),
+            MapKeysError::Map(ref e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: fmt::Debug, E: std::error::Error> std::error::Error for MapKeysError<K, E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            MapKeysError::Map(ref e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+/// Extend `Option` and `Vec` with a `try_map_full` method that returns the input
+/// that caused the error along with it.
+///
+/// The closure borrows the input, so that it can be handed back on failure.
+///
+/// # Type parameters
+///
+/// - `T`: The input value type
+/// - `U`: The output value type
+/// - `E`: The possible error during the mapping
+pub trait FallibleMapFullExt<T, U, E> {
+    /// The container of the converted values
+    type Output;
+
+    /// Apply a fallible map function, returning the offending input along with the error
+    fn try_map_full<F>(self, f: F) -> Result<Self::Output, TryMapError<T, E>> where
+        F: FnMut(&T) -> Result<U, E>;
+}
+
+impl<T, U, E> FallibleMapFullExt<T, U, E> for Option<T> {
+    type Output = Option<U>;
+
+    fn try_map_full<F>(self, mut f: F) -> Result<Option<U>, TryMapError<T, E>> where
+        F: FnMut(&T) -> Result<U, E>
+    {
+        match self {
+            Some(input) => match f(&input) {
+                Ok(u) => Ok(Some(u)),
+                Err(error) => Err(TryMapError { input, error }),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, U, E> FallibleMapFullExt<T, U, E> for Vec<T> {
+    type Output = Vec<U>;
+
+    fn try_map_full<F>(self, mut f: F) -> Result<Vec<U>, TryMapError<T, E>> where
+        F: FnMut(&T) -> Result<U, E>
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        for input in self {
+            match f(&input) {
+                Ok(u) => result_vec.push(u),
+                Err(error) => return Err(TryMapError { input, error }),
+            }
+        }
+        Ok(result_vec)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use {FallibleMapFullExt, TryMapError};
+
+    #[test]
+    fn test_try_map_full_option() {
+        assert_eq!(Some("42").try_map_full(|s| s.parse::<i32>()), Ok(Some(42)));
+        let e = Some("x".to_string()).try_map_full(|s| s.parse::<i32>()).unwrap_err();
+        assert_eq!(e.input, "x");
+        assert_eq!(e.to_string(), "invalid digit found in string");
+    }
+
+    #[test]
+    fn test_try_map_full_vec() {
+        let x = vec!["1", "2"].try_map_full(|s| s.parse::<i32>().map_err(|_| ()));
+        assert_eq!(x, Ok(vec![1, 2]));
+        let x = vec!["1", "x", "y"].try_map_full(|s| s.parse::<i32>().map_err(|_| ()));
+        assert_eq!(x, Err(TryMapError { input: "x", error: () }));
+    }
+}
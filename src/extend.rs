@@ -0,0 +1,90 @@
+/// Extend every `Extend` collection with a `try_extend` method that consumes an
+/// iterator of `Result`s.
+///
+/// The values are inserted until the first error, which is returned. The items
+/// inserted before it are kept in the collection.
+///
+/// # Type parameters
+///
+/// - `T`: The type of the inserted values
+pub trait TryExtend<T> {
+
+    /// Extend the collection with the values of `iter`, stopping at the first error
+    fn try_extend<E, I>(&mut self, iter: I) -> Result<(), E> where
+        I: IntoIterator<Item = Result<T, E>>;
+
+}
+
+impl<C, T> TryExtend<T> for C where
+    C: Extend<T>
+{
+    fn try_extend<E, I>(&mut self, iter: I) -> Result<(), E> where
+        I: IntoIterator<Item = Result<T, E>>
+    {
+        let mut error = None;
+        self.extend(UntilErr { iter: iter.into_iter(), error: &mut error });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Yields the values of an iterator of `Result`s, and stores the first error
+/// instead of yielding it
+struct UntilErr<'a, I, E: 'a> {
+    iter: I,
+    error: &'a mut Option<E>,
+}
+
+impl<'a, I, T, E> Iterator for UntilErr<'a, I, E> where
+    I: Iterator<Item = Result<T, E>>
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.error.is_some() {
+            return None;
+        }
+        match self.iter.next() {
+            Some(Ok(t)) => Some(t),
+            Some(Err(e)) => {
+                *self.error = Some(e);
+                None
+            },
+            None => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.error.is_some() {
+            (0, Some(0))
+        } else {
+            (0, self.iter.size_hint().1)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use TryExtend;
+
+    #[test]
+    fn test_try_extend_vec() {
+        let mut v = vec![1];
+        assert_eq!(v.try_extend(vec![Ok(2), Ok(3)]), Ok::<_, &'static str>(()));
+        assert_eq!(v.try_extend(vec![Ok(4), Err("oh noes"), Ok(5)]), Err("oh noes"));
+        assert_eq!(v, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_try_extend_map() {
+        let mut m = HashMap::new();
+        let r = m.try_extend(vec!["1", "2", "x", "3"].into_iter().map(|s| s.parse::<i32>().map(|k| (k, s))));
+        assert!(r.is_err());
+        assert_eq!(m.len(), 2);
+        assert_eq!(m[&2], "2");
+    }
+}
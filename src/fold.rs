@@ -0,0 +1,60 @@
+use alloc::vec::Vec;
+
+/// Extend `Vec` with a fallible map method that threads an accumulator through the elements
+///
+/// This is a fallible "map-accumulate": the closure receives the accumulator
+/// along with each element, and returns the updated accumulator along with the
+/// mapped value. It computes running statistics, such as totals or maximums,
+/// in the same pass as the transformation, without a `RefCell` or a second
+/// pass. The mapping stops at the first error.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Vec`'s value type
+/// - `U`: The output `Vec`'s value type
+/// - `E`: The possible error during the mapping
+pub trait FallibleMapFoldExt<T, U, E> {
+
+    /// Try to apply a fallible map function to every element, threading an accumulator
+    fn try_map_fold<A, F>(self, init: A, f: F) -> Result<(A, Vec<U>), E> where
+        F: FnMut(A, T) -> Result<(A, U), E>;
+
+}
+
+impl<T, U, E> FallibleMapFoldExt<T, U, E> for Vec<T> {
+    fn try_map_fold<A, F>(self, init: A, mut f: F) -> Result<(A, Vec<U>), E> where
+        F: FnMut(A, T) -> Result<(A, U), E>
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        let mut acc = init;
+        for t in self {
+            let (next, u) = f(acc, t)?;
+            acc = next;
+            result_vec.push(u);
+        }
+        Ok((acc, result_vec))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use FallibleMapFoldExt;
+
+    #[test]
+    fn test_try_map_fold_1() {
+        let x = vec!["1", "22", "333"];
+        let result = x.try_map_fold(0, |max_len, s| s.parse::<i32>().map(|n| (max_len.max(s.len()), n)));
+        assert_eq!(result, Ok((3, vec![1, 22, 333])));
+    }
+
+    #[test]
+    fn test_try_map_fold_2() {
+        let mut calls = 0;
+        let x = vec![1, -2, 3].try_map_fold(0, |total, x| {
+            calls += 1;
+            if x > 0 { Ok((total + x, x * 10)) } else { Err(x) }
+        });
+        assert_eq!(x, Err(-2));
+        assert_eq!(calls, 2);
+    }
+}
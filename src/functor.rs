@@ -0,0 +1,423 @@
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+use core::ops::{Bound, ControlFlow, Range, RangeInclusive};
+use core::task::Poll;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+/// A container whose elements can be mapped with a fallible function
+///
+/// This emulates higher-kinded types with a generic associated type: `Mapped<U>`
+/// is "the same kind of container, holding `U`s instead". It allows writing
+/// `try_fmap` once for generic code, and lets any container, including
+/// user-defined wrapper types, opt in with a single impl.
+///
+/// The mapping stops at the first error.
+pub trait TryFunctor {
+    /// The type of the elements
+    type Item;
+    /// The same container holding elements of type `U`
+    type Mapped<U>;
+
+    /// Apply a fallible map function to every element, stopping at the first error
+    fn try_fmap<U, E, F>(self, f: F) -> Result<Self::Mapped<U>, E> where
+        F: FnMut(Self::Item) -> Result<U, E>;
+}
+
+impl<T> TryFunctor for Option<T> {
+    type Item = T;
+    type Mapped<U> = Option<U>;
+
+    fn try_fmap<U, E, F>(self, f: F) -> Result<Option<U>, E> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        self.map(f).transpose()
+    }
+}
+
+impl<T, const N: usize> TryFunctor for [T; N] {
+    type Item = T;
+    type Mapped<U> = [U; N];
+
+    fn try_fmap<U, E, F>(self, mut f: F) -> Result<[U; N], E> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        let mut error = None;
+        let values = self.map(|t| match error {
+            Some(_) => None,
+            None => f(t).map_err(|e| error = Some(e)).ok(),
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(values.map(Option::unwrap)),
+        }
+    }
+}
+
+/// Maps the endpoint, if any
+impl<T> TryFunctor for Bound<T> {
+    type Item = T;
+    type Mapped<U> = Bound<U>;
+
+    fn try_fmap<U, E, F>(self, mut f: F) -> Result<Bound<U>, E> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        Ok(match self {
+            Bound::Included(t) => Bound::Included(f(t)?),
+            Bound::Excluded(t) => Bound::Excluded(f(t)?),
+            Bound::Unbounded => Bound::Unbounded,
+        })
+    }
+}
+
+/// Maps both endpoints, the start first
+impl<T> TryFunctor for Range<T> {
+    type Item = T;
+    type Mapped<U> = Range<U>;
+
+    fn try_fmap<U, E, F>(self, mut f: F) -> Result<Range<U>, E> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        Ok(f(self.start)?..f(self.end)?)
+    }
+}
+
+/// Maps both endpoints, the start first
+///
+/// Whether the range was exhausted by iterating isn't preserved.
+impl<T> TryFunctor for RangeInclusive<T> {
+    type Item = T;
+    type Mapped<U> = RangeInclusive<U>;
+
+    fn try_fmap<U, E, F>(self, mut f: F) -> Result<RangeInclusive<U>, E> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        let (start, end) = self.into_inner();
+        Ok(f(start)?..=f(end)?)
+    }
+}
+
+/// Maps the ready value
+///
+/// For the `Poll<Option<T>>` of streams, nest the calls:
+/// `poll.try_fmap(|item| item.try_fmap(f))`.
+impl<T> TryFunctor for Poll<T> {
+    type Item = T;
+    type Mapped<U> = Poll<U>;
+
+    fn try_fmap<U, E, F>(self, mut f: F) -> Result<Poll<U>, E> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        Ok(match self {
+            Poll::Ready(t) => Poll::Ready(f(t)?),
+            Poll::Pending => Poll::Pending,
+        })
+    }
+}
+
+/// Maps the `Continue` payload; see `FallibleMapBreakExt` for the `Break` payload
+impl<B, C> TryFunctor for ControlFlow<B, C> {
+    type Item = C;
+    type Mapped<U> = ControlFlow<B, U>;
+
+    fn try_fmap<U, E, F>(self, mut f: F) -> Result<ControlFlow<B, U>, E> where
+        F: FnMut(C) -> Result<U, E>
+    {
+        Ok(match self {
+            ControlFlow::Continue(c) => ControlFlow::Continue(f(c)?),
+            ControlFlow::Break(b) => ControlFlow::Break(b),
+        })
+    }
+}
+
+/// Maps the `Ok` value, keeping the error
+impl<T, X> TryFunctor for Result<T, X> {
+    type Item = T;
+    type Mapped<U> = Result<U, X>;
+
+    fn try_fmap<U, E, F>(self, mut f: F) -> Result<Result<U, X>, E> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        Ok(match self {
+            Ok(t) => Ok(f(t)?),
+            Err(x) => Err(x),
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> TryFunctor for Vec<T> {
+    type Item = T;
+    type Mapped<U> = Vec<U>;
+
+    fn try_fmap<U, E, F>(self, f: F) -> Result<Vec<U>, E> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        self.into_iter().map(f).collect()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> TryFunctor for Box<T> {
+    type Item = T;
+    type Mapped<U> = Box<U>;
+
+    fn try_fmap<U, E, F>(self, mut f: F) -> Result<Box<U>, E> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        f(*self).map(Box::new)
+    }
+}
+
+/// Maps the values, keeping the keys
+#[cfg(feature = "alloc")]
+impl<K: Ord, V> TryFunctor for BTreeMap<K, V> {
+    type Item = V;
+    type Mapped<U> = BTreeMap<K, U>;
+
+    fn try_fmap<U, E, F>(self, mut f: F) -> Result<BTreeMap<K, U>, E> where
+        F: FnMut(V) -> Result<U, E>
+    {
+        self.into_iter().map(|(k, v)| f(v).map(|u| (k, u))).collect()
+    }
+}
+
+/// Maps the values, keeping the keys
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, S: BuildHasher + Default> TryFunctor for HashMap<K, V, S> {
+    type Item = V;
+    type Mapped<U> = HashMap<K, U, S>;
+
+    fn try_fmap<U, E, F>(self, mut f: F) -> Result<HashMap<K, U, S>, E> where
+        F: FnMut(V) -> Result<U, E>
+    {
+        self.into_iter().map(|(k, v)| f(v).map(|u| (k, u))).collect()
+    }
+}
+
+/// Extend `ControlFlow` with a fallible map method for the `Break` payload
+///
+/// The `Continue` payload is mapped with `try_fmap`.
+///
+/// # Type parameters
+///
+/// - `B`: The input `Break` payload type
+/// - `C`: The `Continue` payload type
+pub trait FallibleMapBreakExt<B, C> {
+
+    /// Try to apply a fallible map function to the `Break` payload
+    fn try_map_break<B2, E, F>(self, f: F) -> Result<ControlFlow<B2, C>, E> where
+        F: FnOnce(B) -> Result<B2, E>;
+
+}
+
+impl<B, C> FallibleMapBreakExt<B, C> for ControlFlow<B, C> {
+    fn try_map_break<B2, E, F>(self, f: F) -> Result<ControlFlow<B2, C>, E> where
+        F: FnOnce(B) -> Result<B2, E>
+    {
+        Ok(match self {
+            ControlFlow::Continue(c) => ControlFlow::Continue(c),
+            ControlFlow::Break(b) => ControlFlow::Break(f(b)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {FallibleMapBreakExt, TryFunctor};
+
+    fn parse_all<C: TryFunctor<Item = &'static str>>(c: C) -> Result<C::Mapped<i32>, ::core::num::ParseIntError> {
+        c.try_fmap(|s| s.parse())
+    }
+
+    #[test]
+    fn test_try_fmap_generic() {
+        assert_eq!(parse_all(Some("42")), Ok(Some(42)));
+        assert_eq!(parse_all(["1", "2"]), Ok([1, 2]));
+        assert!(parse_all(["1", "x"]).is_err());
+        assert_eq!(parse_all(Ok::<_, u8>("1")), Ok(Ok(1)));
+        assert_eq!(parse_all(Err::<&str, _>(2u8)), Ok(Err(2)));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_try_fmap_vec() {
+        assert_eq!(parse_all(vec!["1", "2"]), Ok(vec![1, 2]));
+        assert!(parse_all(vec!["1", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_try_fmap_array_short_circuits() {
+        let mut calls = 0;
+        let x = [1, -2, 3].try_fmap(|x| {
+            calls += 1;
+            if x > 0 { Ok(x) } else { Err(x) }
+        });
+        assert_eq!(x, Err(-2));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_try_fmap_ranges() {
+        use core::ops::Bound;
+
+        assert_eq!(parse_all("1".."10"), Ok(1..10));
+        assert_eq!(parse_all("1"..="10"), Ok(1..=10));
+        assert!(parse_all("1".."x").is_err());
+        assert_eq!(parse_all(Bound::Excluded("5")), Ok(Bound::Excluded(5)));
+        assert_eq!(parse_all(Bound::Unbounded), Ok(Bound::Unbounded));
+    }
+
+    #[test]
+    fn test_try_fmap_poll() {
+        use core::task::Poll;
+
+        assert_eq!(parse_all(Poll::Ready("42")), Ok(Poll::Ready(42)));
+        assert_eq!(parse_all(Poll::Pending), Ok(Poll::Pending));
+        let item: Poll<Option<&str>> = Poll::Ready(Some("x"));
+        assert!(item.try_fmap(|o| o.try_fmap(|s| s.parse::<i32>())).is_err());
+        let item: Poll<Option<&str>> = Poll::Ready(None);
+        assert_eq!(item.try_fmap(|o| o.try_fmap(|s| s.parse::<i32>())), Ok(Poll::Ready(None)));
+    }
+
+    #[test]
+    fn test_try_fmap_control_flow() {
+        use core::ops::ControlFlow;
+
+        let x: ControlFlow<&str, &str> = ControlFlow::Continue("42");
+        assert_eq!(parse_all(x), Ok(ControlFlow::Continue(42)));
+        let x: ControlFlow<&str, &str> = ControlFlow::Break("x");
+        assert_eq!(parse_all(x), Ok(ControlFlow::Break("x")));
+        assert!(x.try_map_break(|s| s.parse::<i32>()).is_err());
+        let x: ControlFlow<&str, ()> = ControlFlow::Break("42");
+        assert_eq!(x.try_map_break(|s| s.parse::<i32>()), Ok(ControlFlow::Break(42)));
+    }
+
+    #[test]
+    fn test_try_fmap_user_defined() {
+        struct Pair<T>(T, T);
+
+        impl<T> TryFunctor for Pair<T> {
+            type Item = T;
+            type Mapped<U> = Pair<U>;
+
+            fn try_fmap<U, E, F>(self, mut f: F) -> Result<Pair<U>, E> where
+                F: FnMut(T) -> Result<U, E>
+            {
+                Ok(Pair(f(self.0)?, f(self.1)?))
+            }
+        }
+
+        let Pair(a, b) = parse_all(Pair("1", "2")).unwrap();
+        assert_eq!((a, b), (1, 2));
+    }
+
+    #[cfg(all(feature = "derive", feature = "std"))]
+    #[test]
+    fn test_derive_struct() {
+        use std::marker::PhantomData;
+        use TryMap;
+
+        #[derive(TryMap, Debug, PartialEq)]
+        struct Tuple<'a, T, X: Clone>(&'a str, pub T, Option<Vec<T>>, [T; 2], X, PhantomData<T>) where X: Default;
+
+        let x = Tuple("a", "1", Some(vec!["2", "3"]), ["4", "5"], 6u8, PhantomData);
+        assert_eq!(parse_all(x), Ok(Tuple("a", 1, Some(vec![2, 3]), [4, 5], 6u8, PhantomData)));
+        let x = Tuple("a", "1", Some(vec!["2", "x"]), ["4", "5"], 6u8, PhantomData);
+        assert!(parse_all(x).is_err());
+
+        #[derive(TryMap, Debug, PartialEq)]
+        struct Named<T = &'static str> {
+            /// A documented field
+            pub(crate) pair: (T, u8),
+            nested: ((T,), usize),
+        }
+
+        let x: Named = Named { pair: ("1", 1), nested: (("2",), 2) };
+        assert_eq!(parse_all(x), Ok(Named { pair: (1, 1), nested: ((2,), 2) }));
+
+        #[derive(TryMap, Debug, PartialEq)]
+        struct Lookup<T> {
+            found: Result<T, String>,
+            all: Vec<Result<T, String>>,
+        }
+
+        let x = Lookup { found: Ok("1"), all: vec![Ok("2"), Err("missing".to_string())] };
+        assert_eq!(parse_all(x), Ok(Lookup { found: Ok(1), all: vec![Ok(2), Err("missing".to_string())] }));
+        let x = Lookup { found: Ok("x"), all: vec![] };
+        assert!(parse_all(x).is_err());
+    }
+
+    #[cfg(all(feature = "derive", feature = "std"))]
+    #[test]
+    fn test_derive_all_errors() {
+        use TryMap;
+
+        fn parse(s: &str) -> Result<i32, String> {
+            s.parse().map_err(|_| format!("{} is not a number", s))
+        }
+
+        #[derive(TryMap, Debug, PartialEq)]
+        #[try_map(all_errors)]
+        struct Form<T> {
+            name: &'static str,
+            age: T,
+            scores: Vec<T>,
+            limits: Option<(T, T)>,
+        }
+
+        let x = Form { name: "a", age: "1", scores: vec!["2", "3"], limits: Some(("4", "5")) };
+        assert_eq!(x.try_fmap_all(parse), Ok(Form { name: "a", age: 1, scores: vec![2, 3], limits: Some((4, 5)) }));
+        let x = Form { name: "a", age: "x", scores: vec!["2", "y", "z"], limits: Some(("4", "w")) };
+        let errors = vec!["x is not a number".to_string(), "y is not a number".to_string(), "w is not a number".to_string()];
+        assert_eq!(x.try_fmap_all(parse), Err(errors));
+
+        #[derive(TryMap, Debug, PartialEq)]
+        #[try_map(all_errors)]
+        enum Shape<T> {
+            Point,
+            Rect(T, T),
+        }
+
+        assert_eq!(Shape::Point.try_fmap_all(parse), Ok(Shape::Point));
+        assert_eq!(Shape::Rect("1", "2").try_fmap_all(parse), Ok(Shape::Rect(1, 2)));
+        assert_eq!(Shape::Rect("x", "y").try_fmap_all(parse).map_err(|es| es.len()), Err(2));
+    }
+
+    #[cfg(all(feature = "derive", feature = "std"))]
+    #[test]
+    fn test_derive_enum() {
+        use TryMap;
+
+        #[derive(TryMap, Debug, PartialEq)]
+        enum Tree<'a, T> {
+            Leaf(T),
+            Node { label: &'a str, children: Vec<Tree<'a, T>> },
+            /// An empty subtree
+            Empty,
+        }
+
+        let x = Tree::Node { label: "root", children: vec![Tree::Leaf("1"), Tree::Empty, Tree::Node { label: "inner", children: vec![Tree::Leaf("2")] }] };
+        let y = Tree::Node { label: "root", children: vec![Tree::Leaf(1), Tree::Empty, Tree::Node { label: "inner", children: vec![Tree::Leaf(2)] }] };
+        assert_eq!(parse_all(x), Ok(y));
+        let mut calls = 0;
+        let x = Tree::Node { label: "root", children: vec![Tree::Leaf("1"), Tree::Leaf("x"), Tree::Leaf("3")] };
+        assert!(x.try_fmap(|s| { calls += 1; s.parse::<i32>() }).is_err());
+        assert_eq!(calls, 2);
+
+        #[derive(TryMap, Debug, PartialEq)]
+        enum Either<L, R> {
+            Left(L),
+            Right(R),
+        }
+
+        assert_eq!(Either::<_, u8>::Left("1").try_fmap(|s| s.parse::<u8>()), Ok(Either::Left(1)));
+        assert_eq!(Either::<&str, _>::Right(2).try_fmap(|s| s.parse::<u8>()), Ok(Either::Right(2)));
+    }
+}
@@ -0,0 +1,1237 @@
+//! Async counterparts of the fallible mapping methods, enabled by the `async` feature
+//!
+//! The combinators over `Vec` and tuples additionally need the `alloc` feature.
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::collections::VecDeque;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+use core::future::Future;
+#[cfg(feature = "alloc")]
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+#[cfg(feature = "alloc")]
+use FlipAllExt;
+
+/// Extend `Option` with a fallible, asynchronous map method
+///
+/// This is useful for mapping an optional value through an async operation that
+/// may fail, such as a database lookup of an optional ID. The result is a future
+/// of `Result<Option<U>>`, so the error can be handled with `?` after awaiting.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Option`'s value type
+/// - `U`: The outputs `Option`'s value type
+/// - `E`: The possible error during the mapping
+pub trait FallibleMapAsyncExt<T, U, E> {
+
+    /// Try to apply a fallible async map function to the option
+    fn try_map_async<F, Fut>(self, f: F) -> FlipFuture<Fut> where
+        F: FnOnce(T) -> Fut,
+        Fut: Future<Output = Result<U, E>>;
+
+}
+
+impl<T, U, E> FallibleMapAsyncExt<T, U, E> for Option<T> {
+    fn try_map_async<F, Fut>(self, f: F) -> FlipFuture<Fut> where
+        F: FnOnce(T) -> Fut,
+        Fut: Future<Output = Result<U, E>>
+    {
+        self.map(f).flip_future()
+    }
+}
+
+/// Extend `Option` with a fallible, asynchronous map method that takes an async closure
+///
+/// This is like `try_map_async`, but bounded by `AsyncFnOnce` instead of a closure
+/// returning a future, so async closures that capture by reference work without
+/// boxing or lifetime gymnastics. Enabled by the `async-fn` feature, which
+/// requires Rust 1.85.
+///
+/// ```edition2021
+/// use std::future::Future;
+/// use std::pin::pin;
+/// use std::task::{Context, Poll, Waker};
+/// use try_map::FallibleMapAsyncFnExt;
+///
+/// fn block_on<F: Future>(fut: F) -> F::Output {
+///     let mut fut = pin!(fut);
+///     let mut cx = Context::from_waker(Waker::noop());
+///     loop {
+///         if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+///             return out;
+///         }
+///     }
+/// }
+///
+/// # fn main() {
+/// let names = vec!["zero", "one"];
+/// let name = async |i: usize| names.get(i).copied().ok_or(i);
+/// assert_eq!(block_on(Some(1).try_map_async_fn(name)), Ok(Some("one")));
+/// assert_eq!(block_on(Some(2).try_map_async_fn(name)), Err(2));
+/// # }
+/// ```
+///
+/// Only `Option` is supported. Mapping a `Vec` would call the closure many
+/// times, and each call returns a future that borrows the closure, so the
+/// returned future would need to own the closure and a future borrowing it at
+/// the same time. That takes an `async` block, which isn't available in this
+/// crate's 2015 edition, or unsafe self-referential code. For `Vec`, use
+/// `try_map_concurrent_async_fn`, which borrows the closure instead.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Option`'s value type
+/// - `U`: The outputs `Option`'s value type
+/// - `E`: The possible error during the mapping
+#[cfg(feature = "async-fn")]
+pub trait FallibleMapAsyncFnExt<T, U, E> {
+
+    /// Try to apply a fallible async closure to the option
+    fn try_map_async_fn<F>(self, f: F) -> impl Future<Output = Result<Option<U>, E>> where
+        F: AsyncFnOnce(T) -> Result<U, E>;
+
+}
+
+#[cfg(feature = "async-fn")]
+impl<T, U, E> FallibleMapAsyncFnExt<T, U, E> for Option<T> {
+    fn try_map_async_fn<F>(self, f: F) -> impl Future<Output = Result<Option<U>, E>> where
+        F: AsyncFnOnce(T) -> Result<U, E>
+    {
+        self.map(|t| f(t)).flip_future()
+    }
+}
+
+/// Extend `Option` with a fallible, asynchronous filter method
+///
+/// The predicate receives a reference to the value and returns a future of
+/// `Result<bool>`. The resulting future yields `Ok(Some(t))` if the predicate
+/// resolved to `true`, `Ok(None)` if it resolved to `false` or the option was
+/// empty, and the error if the predicate failed.
+///
+/// There's no variant bounded by `AsyncFnOnce(&T)`: the predicate's future
+/// would borrow the value that the returned future holds on to and hands back,
+/// which is the self-referential shape described at `try_map_async_fn`.
+///
+/// # Type parameters
+///
+/// - `T`: The `Option`'s value type
+/// - `E`: The possible error during the filtering
+pub trait FallibleFilterAsyncExt<T, E> {
+
+    /// Try to filter the option with a fallible async predicate
+    fn try_filter_async<P, Fut>(self, predicate: P) -> TryFilterAsync<T, Fut> where
+        P: FnOnce(&T) -> Fut,
+        Fut: Future<Output = Result<bool, E>>;
+
+}
+
+impl<T, E> FallibleFilterAsyncExt<T, E> for Option<T> {
+    fn try_filter_async<P, Fut>(self, predicate: P) -> TryFilterAsync<T, Fut> where
+        P: FnOnce(&T) -> Fut,
+        Fut: Future<Output = Result<bool, E>>
+    {
+        let fut = self.as_ref().map(predicate);
+        TryFilterAsync { value: self, fut }
+    }
+}
+
+/// Future returned by `try_filter_async`
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct TryFilterAsync<T, Fut> {
+    value: Option<T>,
+    fut: Option<Fut>,
+}
+
+impl<T, Fut, E> Future for TryFilterAsync<T, Fut> where
+    Fut: Future<Output = Result<bool, E>>
+{
+    type Output = Result<Option<T>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // Only `fut` is structurally pinned; `value` is never pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let fut = match this.fut {
+            Some(ref mut fut) => unsafe { Pin::new_unchecked(fut) },
+            None => return Poll::Ready(Ok(None)),
+        };
+        match fut.poll(cx) {
+            Poll::Ready(Ok(true)) => Poll::Ready(Ok(this.value.take())),
+            Poll::Ready(Ok(false)) => Poll::Ready(Ok(None)),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Extend `Option` and `Vec` with a fallible, asynchronous map method that gives
+/// every mapping a deadline
+///
+/// Each mapping is raced against a timer future made by `timer`, and fails with
+/// `E::from(Elapsed)` if the timer completes first. The crate doesn't depend on
+/// a runtime, so the timer comes from the caller, e.g.
+/// `|| tokio::time::sleep(Duration::from_secs(1))`. A lookup that never answers
+/// then fails on its own instead of hanging the whole batch.
+///
+/// The futures of a `Vec` are driven concurrently, like those of `flip_future`,
+/// and the timers all start when the returned future is first polled. As soon
+/// as one mapping fails or times out, the rest are dropped.
+///
+/// # Type parameters
+///
+/// - `T`: The input container's value type
+/// - `U`: The output container's value type
+/// - `E`: The possible error during the mapping, which a timeout converts into
+pub trait FallibleMapAsyncTimeoutExt<T, U, E> {
+    /// The future returned by `try_map_async_timeout`
+    type Future<Fut, D> where
+        Fut: Future<Output = Result<U, E>>,
+        D: Future<Output = ()>,
+        E: From<Elapsed>;
+
+    /// Try to apply a fallible async map function to every element, failing any mapping that outlives its timer
+    fn try_map_async_timeout<F, Fut, G, D>(self, timer: G, f: F) -> Self::Future<Fut, D> where
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = Result<U, E>>,
+        G: FnMut() -> D,
+        D: Future<Output = ()>,
+        E: From<Elapsed>;
+
+}
+
+impl<T, U, E> FallibleMapAsyncTimeoutExt<T, U, E> for Option<T> {
+    type Future<Fut, D> = FlipFuture<Timeout<Fut, D>> where
+        Fut: Future<Output = Result<U, E>>,
+        D: Future<Output = ()>,
+        E: From<Elapsed>;
+
+    fn try_map_async_timeout<F, Fut, G, D>(self, mut timer: G, mut f: F) -> FlipFuture<Timeout<Fut, D>> where
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = Result<U, E>>,
+        G: FnMut() -> D,
+        D: Future<Output = ()>,
+        E: From<Elapsed>
+    {
+        self.map(|t| Timeout { fut: f(t), timer: timer() }).flip_future()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, U, E> FallibleMapAsyncTimeoutExt<T, U, E> for Vec<T> {
+    type Future<Fut, D> = FlipJoin<Timeout<Fut, D>> where
+        Fut: Future<Output = Result<U, E>>,
+        D: Future<Output = ()>,
+        E: From<Elapsed>;
+
+    fn try_map_async_timeout<F, Fut, G, D>(self, mut timer: G, mut f: F) -> FlipJoin<Timeout<Fut, D>> where
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = Result<U, E>>,
+        G: FnMut() -> D,
+        D: Future<Output = ()>,
+        E: From<Elapsed>
+    {
+        let futs: Vec<_> = self.into_iter().map(|t| Timeout { fut: f(t), timer: timer() }).collect();
+        futs.flip_future()
+    }
+}
+
+/// The error of a mapping whose timer completed first
+///
+/// Returned, converted into the mapping's error type, by `try_map_async_timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Elapsed;
+
+/// Displays that the mapping timed out
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("the mapping timed out")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Elapsed {}
+
+/// A mapping raced against its timer, as driven by `try_map_async_timeout`
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Timeout<Fut, D> {
+    fut: Fut,
+    timer: D,
+}
+
+impl<Fut, D, U, E> Future for Timeout<Fut, D> where
+    Fut: Future<Output = Result<U, E>>,
+    D: Future<Output = ()>,
+    E: From<Elapsed>
+{
+    type Output = Result<U, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // Both fields are structurally pinned and never moved out of the struct.
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Poll::Ready(r) = unsafe { Pin::new_unchecked(&mut this.fut) }.poll(cx) {
+            return Poll::Ready(r);
+        }
+        match unsafe { Pin::new_unchecked(&mut this.timer) }.poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(E::from(Elapsed))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Extend `Vec` with a fallible, asynchronous map method with bounded concurrency
+///
+/// At most `limit` futures are in flight at once. The output preserves the
+/// order of the input, and as soon as one of the futures fails, the outstanding
+/// ones are dropped and no new ones are started.
+///
+/// By default, the error is the one of the first future to fail, which depends
+/// on timing. Call `lowest_index_error` on the returned future to get the error
+/// of the failing element with the lowest index instead.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Vec`'s value type
+/// - `U`: The output `Vec`'s value type
+/// - `E`: The possible error during the mapping
+#[cfg(feature = "alloc")]
+pub trait FallibleMapConcurrentExt<T, U, E> {
+
+    /// Try to apply a fallible async map function to every element, running at most `limit` at once
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    fn try_map_concurrent<F, Fut>(self, limit: usize, f: F) -> TryMapConcurrent<T, F, Fut> where
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = Result<U, E>>;
+
+}
+
+#[cfg(feature = "alloc")]
+impl<T, U, E> FallibleMapConcurrentExt<T, U, E> for Vec<T> {
+    fn try_map_concurrent<F, Fut>(self, limit: usize, f: F) -> TryMapConcurrent<T, F, Fut> where
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = Result<U, E>>
+    {
+        assert!(limit > 0, "try_map_concurrent: the concurrency limit must be positive");
+        let mut outputs = Vec::with_capacity(self.len());
+        outputs.resize_with(self.len(), || None);
+        TryMapConcurrent {
+            input: self.into_iter().enumerate(),
+            f,
+            limit,
+            in_flight: Vec::with_capacity(limit),
+            outputs,
+            lowest_index_error: false,
+            failed_at: None,
+        }
+    }
+}
+
+/// Extend `Vec` with a fallible, asynchronous map method with bounded concurrency
+/// that takes an async closure
+///
+/// This is like `try_map_concurrent`, but bounded by `AsyncFn`, so async
+/// closures that capture by reference work. The closure is borrowed for the
+/// lifetime of the returned future, since every call in flight borrows it.
+/// For the same reason, there's no `AsyncFnMut` variant: the futures of an
+/// `AsyncFnMut` closure borrow it mutably, so no two of them could be in
+/// flight at once. Enabled by the `async-fn` and `alloc` features.
+///
+/// ```edition2021
+/// # use std::future::Future;
+/// # use std::pin::pin;
+/// # use std::task::{Context, Poll, Waker};
+/// use try_map::FallibleMapConcurrentAsyncFnExt;
+/// # fn block_on<F: Future>(fut: F) -> F::Output {
+/// #     let mut fut = pin!(fut);
+/// #     let mut cx = Context::from_waker(Waker::noop());
+/// #     loop {
+/// #         if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+/// #             return out;
+/// #         }
+/// #     }
+/// # }
+///
+/// # fn main() {
+/// let names = vec!["zero", "one"];
+/// let name = async |i: usize| names.get(i).copied().ok_or(i);
+/// assert_eq!(block_on(vec![1, 0].try_map_concurrent_async_fn(2, &name)), Ok(vec!["one", "zero"]));
+/// assert_eq!(block_on(vec![0, 2].try_map_concurrent_async_fn(2, &name)), Err(2));
+/// # }
+/// ```
+///
+/// The error is the one of the first future to fail. For the error of the
+/// failing element with the lowest index, pass `|t| f(t)` to
+/// `try_map_concurrent` and call `lowest_index_error` on its future.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Vec`'s value type
+/// - `U`: The output `Vec`'s value type
+/// - `E`: The possible error during the mapping
+#[cfg(all(feature = "async-fn", feature = "alloc"))]
+pub trait FallibleMapConcurrentAsyncFnExt<T, U, E> {
+
+    /// Try to apply a fallible async closure to every element, running at most `limit` at once
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    fn try_map_concurrent_async_fn<'a, F>(self, limit: usize, f: &'a F) -> impl Future<Output = Result<Vec<U>, E>> + 'a where
+        F: AsyncFn(T) -> Result<U, E>,
+        T: 'a,
+        U: 'a,
+        E: 'a;
+
+}
+
+#[cfg(all(feature = "async-fn", feature = "alloc"))]
+impl<T, U, E> FallibleMapConcurrentAsyncFnExt<T, U, E> for Vec<T> {
+    fn try_map_concurrent_async_fn<'a, F>(self, limit: usize, f: &'a F) -> impl Future<Output = Result<Vec<U>, E>> + 'a where
+        F: AsyncFn(T) -> Result<U, E>,
+        T: 'a,
+        U: 'a,
+        E: 'a
+    {
+        self.try_map_concurrent(limit, move |t| f(t))
+    }
+}
+
+/// Extend `Option<Future<Output = Result<T>>>`, `Vec<Future<Output = Result<T>>>`
+/// and tuples of such futures with a `flip_future` method that turns them into
+/// a single future of `Result<Option<T>>`, `Result<Vec<T>>` or `Result<(A, B, ...)>`.
+///
+/// This is the async analogue of `flip`, and replaces the
+/// `match opt { Some(f) => f.await.map(Some), None => Ok(None) }` pattern.
+///
+/// Tuples of two to six futures are supported, as long as they share the error type.
+///
+/// The futures of a `Vec` or a tuple are driven concurrently. As soon as one of
+/// them fails, the rest are dropped, cancelling the outstanding work. Call
+/// `lowest_index_error` on the returned future to get a deterministic error,
+/// that of the failing future with the lowest index, rather than of the first
+/// one to fail.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait FutureFlipExt<T, E> {
+    type Future: Future;
+
+    fn flip_future(self) -> Self::Future;
+}
+
+impl<Fut, T, E> FutureFlipExt<T, E> for Option<Fut> where
+    Fut: Future<Output = Result<T, E>>
+{
+    type Future = FlipFuture<Fut>;
+
+    fn flip_future(self) -> FlipFuture<Fut>
+    {
+        FlipFuture { inner: self }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<Fut, T, E> FutureFlipExt<T, E> for Vec<Fut> where
+    Fut: Future<Output = Result<T, E>>
+{
+    type Future = FlipJoin<Fut>;
+
+    fn flip_future(self) -> FlipJoin<Fut>
+    {
+        FlipJoin {
+            elems: self.into_iter().map(|f| MaybeDone::Pending(Box::pin(f))).collect(),
+            lowest_index_error: false,
+        }
+    }
+}
+
+/// Future returned by `flip_future` and `try_map_async`
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct FlipFuture<Fut> {
+    inner: Option<Fut>,
+}
+
+impl<Fut, U, E> Future for FlipFuture<Fut> where
+    Fut: Future<Output = Result<U, E>>
+{
+    type Output = Result<Option<U>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // The inner future is never moved out of the struct, so projecting the pin is sound.
+        let inner = unsafe { self.map_unchecked_mut(|s| &mut s.inner) };
+        match inner.as_pin_mut() {
+            Some(fut) => fut.poll(cx).map(|r| r.map(Some)),
+            None => Poll::Ready(Ok(None)),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+enum MaybeDone<Fut: Future> {
+    Pending(Pin<Box<Fut>>),
+    Done(Fut::Output),
+    Taken,
+}
+
+#[cfg(feature = "alloc")]
+impl<Fut: Future> MaybeDone<Fut> {
+    /// Poll the future if it's still pending, storing its output, whatever it is
+    fn poll_output(&mut self, cx: &mut Context) -> bool {
+        let ready = match *self {
+            MaybeDone::Pending(ref mut fut) => fut.as_mut().poll(cx),
+            _ => return true,
+        };
+        match ready {
+            Poll::Ready(out) => {
+                *self = MaybeDone::Done(out);
+                true
+            },
+            Poll::Pending => false,
+        }
+    }
+
+    fn take_output(&mut self) -> Fut::Output {
+        match ::core::mem::replace(self, MaybeDone::Taken) {
+            MaybeDone::Done(out) => out,
+            _ => unreachable!("all the futures have completed"),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<Fut, T, E> MaybeDone<Fut> where
+    Fut: Future<Output = Result<T, E>>
+{
+    /// Poll the future if it's still pending, returning whether it has completed
+    fn poll_done(&mut self, cx: &mut Context) -> Result<bool, E> {
+        let ready = match *self {
+            MaybeDone::Pending(ref mut fut) => fut.as_mut().poll(cx),
+            _ => return Ok(true),
+        };
+        match ready {
+            Poll::Ready(Ok(t)) => {
+                *self = MaybeDone::Done(Ok(t));
+                Ok(true)
+            },
+            Poll::Ready(Err(e)) => Err(e),
+            Poll::Pending => Ok(false),
+        }
+    }
+
+    fn take_ok(&mut self) -> T {
+        match ::core::mem::replace(self, MaybeDone::Taken) {
+            MaybeDone::Done(Ok(t)) => t,
+            _ => unreachable!("all the futures have completed successfully"),
+        }
+    }
+
+    fn is_err(&self) -> bool {
+        matches!(*self, MaybeDone::Done(Err(_)))
+    }
+
+    fn take_err(&mut self) -> E {
+        match ::core::mem::replace(self, MaybeDone::Taken) {
+            MaybeDone::Done(Err(e)) => e,
+            _ => unreachable!("the future has failed"),
+        }
+    }
+}
+
+/// Future returned by `flip_future` on a `Vec`
+#[must_use = "futures do nothing unless polled"]
+#[cfg(feature = "alloc")]
+pub struct FlipJoin<Fut: Future> {
+    elems: Vec<MaybeDone<Fut>>,
+    lowest_index_error: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl<Fut: Future> FlipJoin<Fut> {
+    /// Return the error of the failing future with the lowest index, rather than
+    /// of the first one to fail
+    ///
+    /// When a future fails, the ones after it are dropped, but the ones before it
+    /// are still driven to completion, since one of them may fail too. The error
+    /// then doesn't depend on the timing of the futures.
+    pub fn lowest_index_error(mut self) -> Self {
+        self.lowest_index_error = true;
+        self
+    }
+}
+
+// The futures are pinned in their own boxes and the outputs are never pinned.
+#[cfg(feature = "alloc")]
+impl<Fut: Future> Unpin for FlipJoin<Fut> {}
+
+#[cfg(feature = "alloc")]
+impl<Fut, T, E> Future for FlipJoin<Fut> where
+    Fut: Future<Output = Result<T, E>>
+{
+    type Output = Result<Vec<T>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut all_done = true;
+        if this.lowest_index_error {
+            // Failed futures keep their error in place until the ones before them are done.
+            let mut failed = false;
+            for elem in &mut this.elems {
+                if failed {
+                    *elem = MaybeDone::Taken;
+                } else {
+                    all_done &= elem.poll_output(cx);
+                    failed = elem.is_err();
+                }
+            }
+            if !all_done {
+                return Poll::Pending;
+            }
+            if let Some(elem) = this.elems.iter_mut().find(|elem| elem.is_err()) {
+                let e = elem.take_err();
+                this.elems.clear();
+                return Poll::Ready(Err(e));
+            }
+            return Poll::Ready(Ok(this.elems.iter_mut().map(MaybeDone::take_ok).collect()));
+        }
+        for elem in &mut this.elems {
+            match elem.poll_done(cx) {
+                Ok(done) => all_done &= done,
+                Err(e) => {
+                    this.elems.clear();
+                    return Poll::Ready(Err(e));
+                },
+            }
+        }
+        if !all_done {
+            return Poll::Pending;
+        }
+        Poll::Ready(Ok(this.elems.iter_mut().map(MaybeDone::take_ok).collect()))
+    }
+}
+
+/// Extend `Vec<Future<Output = Result<T>>>` with a `flip_all_future` method, the
+/// async counterpart of `flip_all`.
+///
+/// Unlike `flip_future`, a failing future doesn't cancel the others: all of them
+/// are driven to completion, and the result contains either all the values or
+/// all the errors, in the order of the input. `collect_errors` on the returned
+/// future collects the errors into another container instead of a `Vec`.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+#[cfg(feature = "alloc")]
+pub trait FutureFlipAllExt<T, E> {
+    type Future: Future<Output = Result<Vec<T>, Vec<E>>>;
+
+    fn flip_all_future(self) -> Self::Future;
+}
+
+#[cfg(feature = "alloc")]
+impl<Fut, T, E> FutureFlipAllExt<T, E> for Vec<Fut> where
+    Fut: Future<Output = Result<T, E>>
+{
+    type Future = FlipJoinAll<Fut, Vec<E>>;
+
+    fn flip_all_future(self) -> FlipJoinAll<Fut, Vec<E>>
+    {
+        FlipJoinAll { elems: self.into_iter().map(|f| MaybeDone::Pending(Box::pin(f))).collect(), errors: PhantomData }
+    }
+}
+
+/// Future returned by `flip_all_future`
+///
+/// `C` is the container the errors are collected into.
+#[must_use = "futures do nothing unless polled"]
+#[cfg(feature = "alloc")]
+pub struct FlipJoinAll<Fut: Future, C> {
+    elems: Vec<MaybeDone<Fut>>,
+    errors: PhantomData<fn() -> C>,
+}
+
+#[cfg(feature = "alloc")]
+impl<Fut: Future, C> FlipJoinAll<Fut, C> {
+    /// Collect the errors into a container of the caller's choice, e.g. a
+    /// `BTreeSet` for deduplication
+    pub fn collect_errors<C2>(self) -> FlipJoinAll<Fut, C2> {
+        FlipJoinAll { elems: self.elems, errors: PhantomData }
+    }
+}
+
+// The futures are pinned in their own boxes and the outputs are never pinned.
+#[cfg(feature = "alloc")]
+impl<Fut: Future, C> Unpin for FlipJoinAll<Fut, C> {}
+
+#[cfg(feature = "alloc")]
+impl<Fut, T, E, C> Future for FlipJoinAll<Fut, C> where
+    Fut: Future<Output = Result<T, E>>,
+    C: Default + Extend<E>
+{
+    type Output = Result<Vec<T>, C>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut all_done = true;
+        for elem in &mut this.elems {
+            all_done &= elem.poll_output(cx);
+        }
+        if !all_done {
+            return Poll::Pending;
+        }
+        let outputs: Vec<_> = this.elems.iter_mut().map(MaybeDone::take_output).collect();
+        Poll::Ready(outputs.flip_all_collect())
+    }
+}
+
+/// Extend iterators of `Future<Output = Result<T>>` with a `flip_ordered` method
+/// that drives a bounded window of them concurrently, like a `FuturesOrdered`
+///
+/// The futures are pulled from the iterator lazily, at most `limit` in flight at
+/// once, so they can be created as they're started, e.g. by `(0..n).map(fetch)`.
+/// The values are in the order the futures were submitted in, whatever order
+/// they complete in. A completed future keeps its slot until the ones before it
+/// have completed too. As soon as one of them fails, the ones in flight are
+/// dropped and no more are pulled from the iterator.
+///
+/// This is `flip_future` on a `Vec` with bounded concurrency, for futures that
+/// don't all exist up front.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+#[cfg(feature = "alloc")]
+pub trait FutureFlipOrderedExt<T, E> {
+    type Future: Future<Output = Result<Vec<T>, E>>;
+
+    /// Drive the futures at most `limit` at once, collecting their values in order
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    fn flip_ordered(self, limit: usize) -> Self::Future;
+}
+
+#[cfg(feature = "alloc")]
+impl<I, T, E> FutureFlipOrderedExt<T, E> for I where
+    I: IntoIterator,
+    I::Item: Future<Output = Result<T, E>>
+{
+    type Future = FlipOrdered<I::IntoIter, T>;
+
+    fn flip_ordered(self, limit: usize) -> FlipOrdered<I::IntoIter, T>
+    {
+        assert!(limit > 0, "flip_ordered: the concurrency limit must be positive");
+        FlipOrdered {
+            input: self.into_iter().fuse(),
+            limit,
+            in_flight: VecDeque::with_capacity(limit),
+            outputs: Vec::new(),
+        }
+    }
+}
+
+/// Future returned by `flip_ordered`
+#[must_use = "futures do nothing unless polled"]
+#[cfg(feature = "alloc")]
+pub struct FlipOrdered<I: Iterator, T> where
+    I::Item: Future
+{
+    input: ::core::iter::Fuse<I>,
+    limit: usize,
+    in_flight: VecDeque<MaybeDone<I::Item>>,
+    outputs: Vec<T>,
+}
+
+// The futures are pinned in their own boxes and nothing else is ever pinned.
+#[cfg(feature = "alloc")]
+impl<I: Iterator, T> Unpin for FlipOrdered<I, T> where I::Item: Future {}
+
+#[cfg(feature = "alloc")]
+impl<I, Fut, T, E> Future for FlipOrdered<I, T> where
+    I: Iterator<Item = Fut>,
+    Fut: Future<Output = Result<T, E>>
+{
+    type Output = Result<Vec<T>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        loop {
+            while this.in_flight.len() < this.limit {
+                match this.input.next() {
+                    Some(fut) => this.in_flight.push_back(MaybeDone::Pending(Box::pin(fut))),
+                    None => break,
+                }
+            }
+            if this.in_flight.is_empty() {
+                return Poll::Ready(Ok(::core::mem::take(&mut this.outputs)));
+            }
+            for elem in &mut this.in_flight {
+                if let Err(e) = elem.poll_done(cx) {
+                    this.in_flight.clear();
+                    return Poll::Ready(Err(e));
+                }
+            }
+            // Only the completed futures at the front can hand over their values without breaking the order.
+            let done = this.in_flight.iter().take_while(|elem| matches!(**elem, MaybeDone::Done(_))).count();
+            if done == 0 {
+                return Poll::Pending;
+            }
+            this.outputs.extend(this.in_flight.drain(..done).map(|mut elem| elem.take_ok()));
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! flip_future_tuple {
+    ($name:ident: $($F:ident $T:ident $f:ident),+) => {
+        /// Future returned by `flip_future` on a tuple
+        #[must_use = "futures do nothing unless polled"]
+        pub struct $name<$($F: Future),+> {
+            $($f: MaybeDone<$F>,)+
+            lowest_index_error: bool,
+        }
+
+        // The futures are pinned in their own boxes and the outputs are never pinned.
+        impl<$($F: Future),+> Unpin for $name<$($F),+> {}
+
+        impl<$($F: Future),+> $name<$($F),+> {
+            /// Return the error of the leftmost failing future, rather than of the
+            /// first one to fail
+            ///
+            /// See `FlipJoin::lowest_index_error`.
+            pub fn lowest_index_error(mut self) -> Self {
+                self.lowest_index_error = true;
+                self
+            }
+
+            fn cancel(&mut self) {
+                $(self.$f = MaybeDone::Taken;)+
+            }
+        }
+
+        impl<$($F, $T,)+ E> FutureFlipExt<($($T,)+), E> for ($($F,)+) where
+            $($F: Future<Output = Result<$T, E>>),+
+        {
+            type Future = $name<$($F),+>;
+
+            fn flip_future(self) -> $name<$($F),+>
+            {
+                let ($($f,)+) = self;
+                $name { $($f: MaybeDone::Pending(Box::pin($f)),)+ lowest_index_error: false }
+            }
+        }
+
+        impl<$($F, $T,)+ E> Future for $name<$($F),+> where
+            $($F: Future<Output = Result<$T, E>>),+
+        {
+            type Output = Result<($($T,)+), E>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+                let this = self.get_mut();
+                let mut all_done = true;
+                if this.lowest_index_error {
+                    let mut failed = false;
+                    $(
+                        if failed {
+                            this.$f = MaybeDone::Taken;
+                        } else {
+                            all_done &= this.$f.poll_output(cx);
+                            failed = this.$f.is_err();
+                        }
+                    )+
+                    if !all_done {
+                        return Poll::Pending;
+                    }
+                    $(
+                        if this.$f.is_err() {
+                            let e = this.$f.take_err();
+                            this.cancel();
+                            return Poll::Ready(Err(e));
+                        }
+                    )+
+                    return Poll::Ready(Ok(($(this.$f.take_ok(),)+)));
+                }
+                $(
+                    match this.$f.poll_done(cx) {
+                        Ok(done) => all_done &= done,
+                        Err(e) => {
+                            this.cancel();
+                            return Poll::Ready(Err(e));
+                        },
+                    }
+                )+
+                if !all_done {
+                    return Poll::Pending;
+                }
+                Poll::Ready(Ok(($(this.$f.take_ok(),)+)))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "alloc")]
+flip_future_tuple!(FlipJoin2: A TA a, B TB b);
+#[cfg(feature = "alloc")]
+flip_future_tuple!(FlipJoin3: A TA a, B TB b, C TC c);
+#[cfg(feature = "alloc")]
+flip_future_tuple!(FlipJoin4: A TA a, B TB b, C TC c, D TD d);
+#[cfg(feature = "alloc")]
+flip_future_tuple!(FlipJoin5: A TA a, B TB b, C TC c, D TD d, F TF f);
+#[cfg(feature = "alloc")]
+flip_future_tuple!(FlipJoin6: A TA a, B TB b, C TC c, D TD d, F TF f, G TG g);
+
+/// Future returned by `try_map_concurrent`
+#[must_use = "futures do nothing unless polled"]
+#[cfg(feature = "alloc")]
+pub struct TryMapConcurrent<T, F, Fut: Future> {
+    input: ::core::iter::Enumerate<::alloc::vec::IntoIter<T>>,
+    f: F,
+    limit: usize,
+    in_flight: Vec<(usize, Pin<Box<Fut>>)>,
+    outputs: Vec<Option<Fut::Output>>,
+    lowest_index_error: bool,
+    failed_at: Option<usize>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F, Fut: Future> TryMapConcurrent<T, F, Fut> {
+    /// Return the error of the failing element with the lowest index, rather than
+    /// of the first one to fail
+    ///
+    /// When a future fails, the ones for later elements are dropped and no new
+    /// ones are started, but the ones for earlier elements are still driven to
+    /// completion, since one of them may fail too. The error then doesn't depend
+    /// on the timing of the futures.
+    pub fn lowest_index_error(mut self) -> Self {
+        self.lowest_index_error = true;
+        self
+    }
+}
+
+// The futures are pinned in their own boxes and nothing else is ever pinned.
+#[cfg(feature = "alloc")]
+impl<T, F, Fut: Future> Unpin for TryMapConcurrent<T, F, Fut> {}
+
+#[cfg(feature = "alloc")]
+impl<T, F, Fut, U, E> Future for TryMapConcurrent<T, F, Fut> where
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<U, E>>
+{
+    type Output = Result<Vec<U>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        loop {
+            while this.in_flight.len() < this.limit && this.failed_at.is_none() {
+                match this.input.next() {
+                    Some((i, t)) => this.in_flight.push((i, Box::pin((this.f)(t)))),
+                    None => break,
+                }
+            }
+            let mut progressed = false;
+            let mut j = 0;
+            while j < this.in_flight.len() {
+                match this.in_flight[j].1.as_mut().poll(cx) {
+                    Poll::Ready(Ok(u)) => {
+                        let (i, _) = this.in_flight.swap_remove(j);
+                        this.outputs[i] = Some(Ok(u));
+                        progressed = true;
+                    },
+                    Poll::Ready(Err(e)) if this.lowest_index_error => {
+                        // Only the earlier elements can still change the error.
+                        let (i, _) = this.in_flight.swap_remove(j);
+                        this.outputs[i] = Some(Err(e));
+                        this.failed_at = Some(i);
+                        this.in_flight.retain(|&(k, _)| k < i);
+                        j = 0;
+                        progressed = true;
+                    },
+                    Poll::Ready(Err(e)) => {
+                        this.in_flight.clear();
+                        return Poll::Ready(Err(e));
+                    },
+                    Poll::Pending => j += 1,
+                }
+            }
+            if let Some(i) = this.failed_at {
+                if this.in_flight.is_empty() {
+                    return match this.outputs[i].take() {
+                        Some(Err(e)) => Poll::Ready(Err(e)),
+                        _ => unreachable!("the future has failed"),
+                    };
+                }
+            } else if this.in_flight.is_empty() && this.input.len() == 0 {
+                let result_vec = this.outputs.drain(..)
+                    .map(|out| match out {
+                        Some(Ok(u)) => u,
+                        _ => unreachable!("all the futures have completed successfully"),
+                    })
+                    .collect();
+                return Poll::Ready(Ok(result_vec));
+            }
+            if !progressed {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::future::{pending, ready, Future};
+    use std::pin::{pin, Pin};
+    use std::task::{Context, Poll, Waker};
+
+    use future::Elapsed;
+    use {FallibleFilterAsyncExt, FallibleMapAsyncExt, FallibleMapAsyncTimeoutExt, FutureFlipExt};
+    #[cfg(feature = "alloc")]
+    use {FallibleMapConcurrentExt, FutureFlipAllExt, FutureFlipOrderedExt};
+
+    pub fn block_on<F: Future>(fut: F) -> F::Output {
+        // `Waker::noop` is newer than the crate's minimum Rust version.
+        struct NoopWake;
+
+        impl ::std::task::Wake for NoopWake {
+            fn wake(self: ::std::sync::Arc<Self>) {}
+        }
+
+        let mut fut = pin!(fut);
+        let waker = Waker::from(::std::sync::Arc::new(NoopWake));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_map_async_1() {
+        let x = Some(42).try_map_async(|x| ready(Ok::<_, &'static str>(x + 1)));
+        assert_eq!(block_on(x), Ok(Some(43)));
+    }
+
+    #[test]
+    fn test_try_map_async_2() {
+        let x = Some(42).try_map_async(|_| ready(Err::<i32, _>("oh noes")));
+        assert_eq!(block_on(x), Err("oh noes"));
+        let x = None.try_map_async(|x: i32| ready(Ok::<_, &'static str>(x + 1)));
+        assert_eq!(block_on(x), Ok(None));
+    }
+
+    #[cfg(feature = "async-fn")]
+    #[test]
+    fn test_try_map_async_fn() {
+        use FallibleMapAsyncFnExt;
+
+        let offset = 1;
+        let add = |x: i32| ready(Ok::<_, &'static str>(x + offset));
+        assert_eq!(block_on(Some(42).try_map_async_fn(add)), Ok(Some(43)));
+        assert_eq!(block_on(None.try_map_async_fn(add)), Ok(None));
+    }
+
+    #[cfg(feature = "async-fn")]
+    #[test]
+    fn test_try_map_concurrent_async_fn() {
+        use FallibleMapConcurrentAsyncFnExt;
+
+        let limit = 10;
+        let check = |x: i32| YieldOnce(Some(if x < limit { Ok(x * 2) } else { Err(x) }), x % 2 == 0);
+        assert_eq!(block_on(vec![1, 2, 3].try_map_concurrent_async_fn(2, &check)), Ok(vec![2, 4, 6]));
+        assert_eq!(block_on(vec![1, 20, 3].try_map_concurrent_async_fn(2, &check)), Err(20));
+    }
+
+    #[test]
+    fn test_try_filter_async() {
+        let check = |x: &i32| YieldOnce(Some(if *x < 0 { Err("oh noes") } else { Ok(*x > 10) }), false);
+        assert_eq!(block_on(Some(42).try_filter_async(check)), Ok(Some(42)));
+        assert_eq!(block_on(Some(1).try_filter_async(check)), Ok(None));
+        assert_eq!(block_on(Some(-1).try_filter_async(check)), Err("oh noes"));
+        assert_eq!(block_on(None.try_filter_async(check)), Ok(None));
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum LookupError {
+        Missing(i32),
+        TimedOut,
+    }
+
+    impl From<Elapsed> for LookupError {
+        fn from(_: Elapsed) -> LookupError {
+            LookupError::TimedOut
+        }
+    }
+
+    #[test]
+    fn test_try_map_async_timeout_option() {
+        let lookup = |x: i32| YieldOnce(Some(if x < 0 { Err(LookupError::Missing(x)) } else { Ok(x * 2) }), x != 1);
+        assert_eq!(block_on(Some(2).try_map_async_timeout(|| ready(()), lookup)), Ok(Some(4)));
+        assert_eq!(block_on(Some(-2).try_map_async_timeout(|| ready(()), lookup)), Err(LookupError::Missing(-2)));
+        assert_eq!(block_on(Some(1).try_map_async_timeout(|| ready(()), lookup)), Err(LookupError::TimedOut));
+        assert_eq!(block_on(Some(1).try_map_async_timeout(pending, lookup)), Ok(Some(2)));
+        assert_eq!(block_on(None.try_map_async_timeout(|| ready(()), lookup)), Ok(None));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_try_map_async_timeout_vec() {
+        let lookup = |x: i32| YieldOnce(Some(if x < 0 { Err(LookupError::Missing(x)) } else { Ok(x * 2) }), x != 1);
+        assert_eq!(block_on(vec![2, 3].try_map_async_timeout(|| ready(()), lookup)), Ok(vec![4, 6]));
+        assert_eq!(block_on(vec![2, 1, 3].try_map_async_timeout(|| ready(()), lookup)), Err(LookupError::TimedOut));
+        assert_eq!(block_on(vec![2, 1, 3].try_map_async_timeout(pending, lookup)), Ok(vec![4, 2, 6]));
+        assert_eq!(block_on(vec![1, -3].try_map_async_timeout(pending, lookup)), Err(LookupError::Missing(-3)));
+    }
+
+    #[test]
+    fn test_flip_future() {
+        assert_eq!(block_on(Some(ready(Ok::<_, ()>(42))).flip_future()), Ok(Some(42)));
+        assert_eq!(block_on(Some(ready(Err::<i32, _>("oh noes"))).flip_future()), Err("oh noes"));
+        assert_eq!(block_on(None::<std::future::Ready<Result<i32, ()>>>.flip_future()), Ok(None));
+    }
+
+    struct YieldOnce<T>(Option<T>, bool);
+
+    impl<T: Unpin> Future for YieldOnce<T> {
+        type Output = T;
+
+        fn poll(mut self: Pin<&mut Self>, _: &mut Context) -> Poll<T> {
+            if self.1 {
+                Poll::Ready(self.0.take().unwrap())
+            } else {
+                self.1 = true;
+                Poll::Pending
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flip_future_vec_1() {
+        let x = vec![YieldOnce(Some(Ok::<_, ()>(1)), false), YieldOnce(Some(Ok(2)), true)];
+        assert_eq!(block_on(x.flip_future()), Ok(vec![1, 2]));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flip_future_vec_2() {
+        let x = vec![YieldOnce(Some(Ok(1)), false), YieldOnce(Some(Err("oh noes")), true), YieldOnce(None, false)];
+        assert_eq!(block_on(x.flip_future()), Err("oh noes"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flip_future_vec_lowest_index_error() {
+        let x = || vec![YieldOnce(Some(Ok(1)), false), YieldOnce(Some(Err("oh noes")), false), YieldOnce(Some(Err("oh foes")), true)];
+        assert_eq!(block_on(x().flip_future()), Err("oh foes"));
+        assert_eq!(block_on(x().flip_future().lowest_index_error()), Err("oh noes"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_try_map_concurrent_1() {
+        let x = vec![3, 1, 2].try_map_concurrent(2, |x| YieldOnce(Some(Ok::<_, ()>(x * 10)), x % 2 == 0));
+        assert_eq!(block_on(x), Ok(vec![30, 10, 20]));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_try_map_concurrent_2() {
+        let mut started = 0;
+        {
+            let x = vec![1, 2, 3, 4, 5].try_map_concurrent(2, |x| {
+                started += 1;
+                YieldOnce(Some(if x == 2 { Err("oh noes") } else { Ok(x) }), true)
+            });
+            assert_eq!(block_on(x), Err("oh noes"));
+        }
+        assert_eq!(started, 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_try_map_concurrent_lowest_index_error() {
+        let mut started = 0;
+        {
+            let x = vec![1, 2, 3, 4].try_map_concurrent(3, |x| {
+                started += 1;
+                YieldOnce(Some(if x == 4 { Ok(x) } else { Err(x) }), x > 1)
+            });
+            assert_eq!(block_on(x.lowest_index_error()), Err(1));
+        }
+        assert_eq!(started, 3);
+        let x = vec![1, 2, 3].try_map_concurrent(3, |x| YieldOnce(Some(if x == 1 { Ok(x) } else { Err(x) }), x > 1));
+        assert_eq!(block_on(x), Err(2));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flip_all_future() {
+        let x = vec![YieldOnce(Some(Ok::<_, &'static str>(1)), false), YieldOnce(Some(Ok(2)), true)];
+        assert_eq!(block_on(x.flip_all_future()), Ok(vec![1, 2]));
+        let x = vec![YieldOnce(Some(Err("oh noes")), false), YieldOnce(Some(Ok(2)), true), YieldOnce(Some(Err("oh foes")), true)];
+        assert_eq!(block_on(x.flip_all_future()), Err(vec!["oh noes", "oh foes"]));
+        let x = vec![YieldOnce(Some(Err::<i32, _>("oh noes")), true), YieldOnce(Some(Err("oh noes")), false)];
+        assert_eq!(block_on(x.flip_all_future().collect_errors::<BTreeSet<_>>()), Err(Some("oh noes").into_iter().collect()));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flip_ordered_1() {
+        let x = || vec![YieldOnce(Some(Ok::<_, ()>(1)), false), YieldOnce(Some(Ok(2)), true), YieldOnce(Some(Ok(3)), false)];
+        assert_eq!(block_on(x().flip_ordered(2)), Ok(vec![1, 2, 3]));
+        assert_eq!(block_on(x().flip_ordered(1)), Ok(vec![1, 2, 3]));
+        assert_eq!(block_on(Vec::<YieldOnce<Result<i32, ()>>>::new().flip_ordered(2)), Ok(vec![]));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flip_ordered_2() {
+        let mut started = 0;
+        {
+            let x = (1..6).map(|x| {
+                started += 1;
+                YieldOnce(Some(if x == 2 { Err("oh noes") } else { Ok(x) }), x != 1)
+            });
+            assert_eq!(block_on(x.flip_ordered(2)), Err("oh noes"));
+        }
+        assert_eq!(started, 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flip_future_tuple_1() {
+        let x = (YieldOnce(Some(Ok::<_, ()>(1)), false), ready(Ok("two")), YieldOnce(Some(Ok(3.0)), true));
+        assert_eq!(block_on(x.flip_future()), Ok((1, "two", 3.0)));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flip_future_tuple_2() {
+        let x = (YieldOnce(Some(Ok(1)), false), ready(Err::<&'static str, _>("oh noes")));
+        assert_eq!(block_on(x.flip_future()), Err("oh noes"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_flip_future_tuple_lowest_index_error() {
+        let x = || (YieldOnce(Some(Err::<i32, _>("oh noes")), false), ready(Err::<&'static str, _>("oh foes")));
+        assert_eq!(block_on(x().flip_future()), Err("oh foes"));
+        assert_eq!(block_on(x().flip_future().lowest_index_error()), Err("oh noes"));
+    }
+}
@@ -0,0 +1,194 @@
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+/// Extend `Vec<Result<T>>` and `Vec<Option<T>>` with a `flip_indexed` method that
+/// works like `flip`, but reports the index of the element that failed.
+///
+/// "Element 3172 failed" is a lot more actionable than a bare error. For
+/// `Vec<Result<T>>`, the error is paired with the index; for `Vec<Option<T>>`,
+/// the index of the first `None` becomes the error.
+#[cfg(feature = "alloc")]
+pub trait FlipIndexedExt {
+    type Output;
+
+    fn flip_indexed(self) -> Self::Output;
+}
+
+#[cfg(feature = "alloc")]
+impl<T, E> FlipIndexedExt for Vec<Result<T, E>> {
+    type Output = Result<Vec<T>, (usize, E)>;
+
+    fn flip_indexed(self) -> Result<Vec<T>, (usize, E)>
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        for (i, t) in self.into_iter().enumerate() {
+            match t {
+                Ok(u) => result_vec.push(u),
+                Err(e) => return Err((i, e)),
+            }
+        }
+        Ok(result_vec)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> FlipIndexedExt for Vec<Option<T>> {
+    type Output = Result<Vec<T>, usize>;
+
+    fn flip_indexed(self) -> Result<Vec<T>, usize>
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        for (i, t) in self.into_iter().enumerate() {
+            match t {
+                Some(u) => result_vec.push(u),
+                None => return Err(i),
+            }
+        }
+        Ok(result_vec)
+    }
+}
+
+/// Extend `Vec<Result<T>>`, `Option<Result<T>>`, `HashMap<K, Result<V>>` and
+/// `BTreeMap<K, Result<V>>` with a `flip_context` method that works like `flip`,
+/// but lets the caller wrap the surfaced error with context.
+///
+/// The closure receives the position of the failing element along with the
+/// error, which a `map_err` after the flip would no longer have access to. For
+/// `Vec` the position is the index, and for `Option<Result<T>>` it's always `0`.
+/// For the maps it's the key of the failing entry; a `HashMap` is flipped in
+/// its iteration order, so which error surfaces is unspecified if several
+/// entries failed.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait FlipContextExt<T, E> {
+    type Output;
+    /// The position passed to the closure: an index, or a key for the maps
+    type Position;
+
+    fn flip_context<E2, F>(self, f: F) -> Result<Self::Output, E2> where
+        F: FnOnce(Self::Position, E) -> E2;
+}
+
+#[cfg(feature = "alloc")]
+impl<T, E> FlipContextExt<T, E> for Vec<Result<T, E>> {
+    type Output = Vec<T>;
+    type Position = usize;
+
+    fn flip_context<E2, F>(self, f: F) -> Result<Vec<T>, E2> where
+        F: FnOnce(usize, E) -> E2
+    {
+        self.flip_indexed().map_err(|(i, e)| f(i, e))
+    }
+}
+
+impl<T, E> FlipContextExt<T, E> for Option<Result<T, E>> {
+    type Output = Option<T>;
+    type Position = usize;
+
+    fn flip_context<E2, F>(self, f: F) -> Result<Option<T>, E2> where
+        F: FnOnce(usize, E) -> E2
+    {
+        match self {
+            Some(r) => r.map(Some).map_err(|e| f(0, e)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Ord, V, E> FlipContextExt<V, E> for BTreeMap<K, Result<V, E>> {
+    type Output = BTreeMap<K, V>;
+    type Position = K;
+
+    fn flip_context<E2, F>(self, f: F) -> Result<BTreeMap<K, V>, E2> where
+        F: FnOnce(K, E) -> E2
+    {
+        let mut result_map = BTreeMap::new();
+        for (k, v) in self {
+            match v {
+                Ok(v) => { result_map.insert(k, v); },
+                Err(e) => return Err(f(k, e)),
+            }
+        }
+        Ok(result_map)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, E, S: BuildHasher + Default> FlipContextExt<V, E> for HashMap<K, Result<V, E>, S> {
+    type Output = HashMap<K, V, S>;
+    type Position = K;
+
+    fn flip_context<E2, F>(self, f: F) -> Result<HashMap<K, V, S>, E2> where
+        F: FnOnce(K, E) -> E2
+    {
+        let mut result_map = HashMap::with_capacity_and_hasher(self.len(), S::default());
+        for (k, v) in self {
+            match v {
+                Ok(v) => { result_map.insert(k, v); },
+                Err(e) => return Err(f(k, e)),
+            }
+        }
+        Ok(result_map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use FlipContextExt;
+    #[cfg(feature = "std")]
+    use FlipIndexedExt;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_flip_indexed_result() {
+        let x: Vec<Result<i32, &'static str>> = vec![Ok(1), Ok(2)];
+        assert_eq!(x.flip_indexed(), Ok(vec![1, 2]));
+        let x = vec![Ok(1), Ok(2), Err("oh noes"), Err("oh foes")];
+        assert_eq!(x.flip_indexed(), Err((2, "oh noes")));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_flip_indexed_option() {
+        assert_eq!(vec![Some(1), Some(2)].flip_indexed(), Ok(vec![1, 2]));
+        assert_eq!(vec![Some(1), None, Some(3)].flip_indexed(), Err(1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_flip_context_vec() {
+        let x = vec![Ok(1), Err("oh noes"), Ok(3)];
+        assert_eq!(x.flip_context(|i, e| format!("element {}: {}", i, e)), Err("element 1: oh noes".to_string()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_flip_context_maps() {
+        use std::collections::{BTreeMap, HashMap};
+
+        let x: BTreeMap<_, Result<i32, _>> = vec![("a", Ok(1)), ("b", Err("oh noes")), ("c", Err("oh foes"))].into_iter().collect();
+        assert_eq!(x.flip_context(|k, e| format!("{}: {}", k, e)), Err("b: oh noes".to_string()));
+        let x: HashMap<_, Result<i32, &str>> = vec![("a", Ok(1)), ("b", Err("oh noes"))].into_iter().collect();
+        assert_eq!(x.flip_context(|k, e| format!("{}: {}", k, e)), Err("b: oh noes".to_string()));
+        let x: HashMap<_, Result<i32, &str>> = vec![("a", Ok(1)), ("b", Ok(2))].into_iter().collect();
+        assert_eq!(x.flip_context(|k, _| k), Ok(vec![("a", 1), ("b", 2)].into_iter().collect()));
+    }
+
+    #[test]
+    fn test_flip_context_option() {
+        let x: Option<Result<i32, &'static str>> = Some(Ok(42));
+        assert_eq!(x.flip_context(|_, e| e.len()), Ok(Some(42)));
+        let x: Option<Result<i32, &'static str>> = Some(Err("oh noes"));
+        assert_eq!(x.flip_context(|_, e| e.len()), Err(7));
+    }
+}
@@ -1,3 +1,229 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "nightly", feature(try_trait_v2, try_trait_v2_residual))]
+
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(feature = "alloc")]
+#[macro_use]
+extern crate alloc;
+#[cfg(feature = "derive")]
+extern crate try_map_derive;
+// Lets the tests use `#[derive(TryMap)]`, whose output refers to `::try_map`
+#[cfg(all(test, feature = "derive"))]
+extern crate self as try_map;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "alloc")]
+mod accumulate;
+mod cell;
+#[cfg(feature = "alloc")]
+mod chain;
+mod combine;
+mod convert;
+mod default;
+mod error;
+mod extend;
+#[cfg(feature = "alloc")]
+mod fold;
+mod functor;
+mod indexed;
+#[cfg(feature = "alloc")]
+mod map;
+#[cfg(feature = "alloc")]
+mod partial;
+mod slice;
+#[cfg(feature = "alloc")]
+mod string;
+#[cfg(feature = "std")]
+mod sync;
+#[cfg(all(test, feature = "std"))]
+mod test_util;
+#[cfg(feature = "threads")]
+mod thread;
+mod traverse;
+#[cfg(feature = "alloc")]
+mod validated;
+mod zip;
+
+pub mod core_only;
+#[cfg(feature = "alloc")]
+pub mod policy;
+pub mod prelude;
+#[cfg(feature = "async")]
+pub mod future;
+#[cfg(feature = "nightly")]
+pub mod nightly;
+
+#[cfg(feature = "alloc")]
+pub use accumulate::{FallibleMapAllExt, FirstOkExt, FlipAllExt, FlipLossyExt, FlipOkExt, FlipOrDefaultExt, IntoErrorsExt, PartitionFlipExt, TryOrElseChainExt};
+pub use cell::{FallibleMapCellExt, FallibleMapRefCellExt, TryOnceExt};
+#[cfg(feature = "alloc")]
+pub use chain::{TryChain, TryChainRun};
+pub use combine::Combine;
+pub use convert::FlipMapErrExt;
+pub use default::{TryDefault, TryDefaultExt};
+pub use error::{FallibleMapFullExt, MapKeysError, TryMapError, ZipByKeyError, ZipMapError};
+pub use extend::TryExtend;
+#[cfg(feature = "alloc")]
+pub use fold::FallibleMapFoldExt;
+pub use functor::{FallibleMapBreakExt, TryFunctor};
+#[cfg(feature = "async")]
+pub use future::{Elapsed, FallibleFilterAsyncExt, FallibleMapAsyncExt, FallibleMapAsyncTimeoutExt, FutureFlipExt};
+#[cfg(all(feature = "async", feature = "alloc"))]
+pub use future::{FallibleMapConcurrentExt, FutureFlipAllExt, FutureFlipOrderedExt};
+#[cfg(feature = "async-fn")]
+pub use future::FallibleMapAsyncFnExt;
+#[cfg(all(feature = "async-fn", feature = "alloc"))]
+pub use future::FallibleMapConcurrentAsyncFnExt;
+pub use indexed::FlipContextExt;
+#[cfg(feature = "alloc")]
+pub use indexed::FlipIndexedExt;
+#[cfg(feature = "alloc")]
+pub use map::{FallibleGroupMapExt, FallibleMapEntriesExt, FallibleMapKeysExt, FallibleMapValuesMutExt, FallibleZipMapByKeyExt, TryEntryExt, TryMergeExt, UnmatchedKeys};
+#[cfg(feature = "nightly")]
+pub use nightly::{FlipAnyExt, TryMapAnyExt};
+#[cfg(feature = "alloc")]
+pub use partial::{FallibleDrainMapExt, FallibleMapPartialExt, FlipResumableExt, PartialFlip, PartialResult};
+#[cfg(feature = "alloc")]
+pub use policy::FlipWithPolicyExt;
+#[cfg(feature = "alloc")]
+pub use slice::{FallibleMapChunksExt, FallibleMapWindowsExt};
+pub use slice::{FallibleMapMutExt, TryBinarySearchExt};
+#[cfg(feature = "alloc")]
+pub use string::FallibleMapCharsExt;
+#[cfg(feature = "std")]
+pub use sync::FallibleMapLockedExt;
+#[cfg(feature = "threads")]
+pub use thread::{FallibleMapMutParallelExt, FallibleMapParallelExt, FlipParallelExt};
+pub use traverse::{Sequence, Traverse};
+#[cfg(feature = "alloc")]
+pub use validated::Validated;
+#[cfg(feature = "alloc")]
+pub use zip::FallibleZipMapExt;
+pub use zip::{FallibleZipExt, FlipZipExt};
+
+/// Derive `TryFunctor` for a struct or an enum, mapping its first type parameter
+///
+/// The generated `try_fmap` converts every field whose type is the parameter
+/// with the closure, and every field that holds it in a `TryFunctor` container,
+/// such as `Option<T>`, `Result<T, X>`, `Vec<T>` or `Box<T>`, or in a tuple,
+/// element by element.
+/// The other fields are moved over unchanged. The conversion stops at the first
+/// error. This turns a DTO into its domain counterpart without writing the
+/// conversion out field by field:
+///
+/// ```
+/// #[macro_use]
+/// extern crate try_map;
+///
+/// use std::net::IpAddr;
+/// use try_map::TryFunctor;
+///
+/// #[derive(TryMap, Debug, PartialEq)]
+/// struct Config<T> {
+///     name: &'static str,
+///     listen: T,
+///     upstreams: [T; 2],
+///     fallback: Option<(T, u16)>,
+/// }
+///
+/// # fn main() {
+/// let dto = Config { name: "proxy", listen: "::1", upstreams: ["10.0.0.1", "10.0.0.2"], fallback: None };
+/// let config: Config<IpAddr> = dto.try_fmap(|s| s.parse()).unwrap();
+/// assert_eq!(config.upstreams[1], IpAddr::from([10, 0, 0, 2]));
+///
+/// let dto = Config { name: "proxy", listen: "::1", upstreams: ["::2", "::3"], fallback: Some(("localhost", 80)) };
+/// assert!(dto.try_fmap(|s| s.parse::<IpAddr>()).is_err());
+/// # }
+/// ```
+///
+/// For an enum, the fields of whichever variant the value holds are converted
+/// the same way. Since the generated impl is itself a `TryFunctor`, recursive
+/// types such as syntax trees work too, with fields like `Box<Expr<T>>` or
+/// `Vec<Expr<T>>`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate try_map;
+///
+/// use try_map::TryFunctor;
+///
+/// #[derive(TryMap, Debug, PartialEq)]
+/// enum Limit<T> {
+///     Exact(T),
+///     Between { min: T, max: Option<T> },
+///     Unlimited,
+/// }
+///
+/// # fn main() {
+/// let limit = Limit::Between { min: "1", max: Some("10") };
+/// assert_eq!(limit.try_fmap(|s| s.parse::<u32>()), Ok(Limit::Between { min: 1, max: Some(10) }));
+/// assert_eq!(Limit::Unlimited.try_fmap(|s: &str| s.parse::<u32>()), Ok(Limit::Unlimited));
+/// assert!(Limit::Exact("-1").try_fmap(|s| s.parse::<u32>()).is_err());
+/// # }
+/// ```
+///
+/// With `#[try_map(all_errors)]` on the type, the derive also generates a
+/// `try_fmap_all` method that goes on converting the fields after a failure,
+/// and returns the errors of all the failed fields in a `Vec`, in field order.
+/// Each field is still converted with `try_fmap`, so it contributes at most one
+/// error. The method needs the `alloc` feature.
+///
+/// The mapped parameter can't have bounds, in the parameter list or in the
+/// `where` clause, as `TryFunctor::Mapped` can't require them:
+///
+/// ```compile_fail
+/// # #[macro_use]
+/// # extern crate try_map;
+/// #[derive(TryMap)]
+/// struct Sorted<T> where T: Ord {
+///     items: Vec<T>,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// In a map, a `Result` or a `ControlFlow`, the parameter must be in the
+/// position that `TryFunctor` maps: the values, the `Ok` value or the
+/// `Continue` payload. Enabled by the `derive` feature.
+#[cfg(feature = "derive")]
+pub use try_map_derive::TryMap;
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "alloc")]
+    pub use alloc::vec::Vec;
+    pub use core::marker::PhantomData;
+    pub use core::ops::FnMut;
+    pub use core::option::Option;
+    pub use core::result::Result;
+
+    /// The erased closure type of the `TryFunctor` impls generated by `#[derive(TryMap)]`
+    pub type DynFnMut<'a, T, U, E> = dyn FnMut(T) -> Result<U, E> + 'a;
+
+    /// Call `f`, giving the `?` operators of a conversion a scope of their own
+    pub fn catch<T, E, F: FnOnce() -> Result<T, E>>(f: F) -> Result<T, E> {
+        f()
+    }
+
+    /// Keep the value of `r`, or move its error into `errors`
+    #[cfg(feature = "alloc")]
+    pub fn collect_error<T, E>(r: Result<T, E>, errors: &mut Vec<E>) -> Option<T> {
+        match r {
+            Ok(t) => Some(t),
+            Err(e) => {
+                errors.push(e);
+                None
+            },
+        }
+    }
+}
+
 /// Extend `Option` with a fallible map method
 ///
 /// This is useful for mapping fallible operations (i.e. operations that)
@@ -16,6 +242,19 @@ pub trait FallibleMapExt<T, U, E> {
     fn try_map<F>(self, f: F) -> Result<Option<U>, E> where
         F: FnOnce(T) -> Result<U, E>;
 
+    /// Try to apply a fallible map function to the option, passing it a mutable state
+    ///
+    /// The state is borrowed only for the duration of the call, which sidesteps
+    /// borrow checker conflicts when the closure would otherwise have to
+    /// capture e.g. `&mut self`.
+    fn try_map_with<S, F>(self, state: &mut S, f: F) -> Result<Option<U>, E> where
+        S: ?Sized,
+        F: FnOnce(&mut S, T) -> Result<U, E>,
+        Self: Sized
+    {
+        self.try_map(|t| f(state, t))
+    }
+
 }
 
 // Implementions
@@ -31,6 +270,86 @@ impl<T, U, E> FallibleMapExt<T, U, E> for Option<T> {
     }
 }
 
+/// Extend `Option` with a fallible map method for closures that return `Option`
+///
+/// This is `try_map` for operations that signal failure with `None` instead of
+/// an error. The result is `Option<Option<U>>`: the outer `None` means that the
+/// closure failed, and `Some(None)` that there was no value to map to begin with,
+/// so the failure can be surfaced with `?` in functions returning `Option`.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Option`'s value type
+/// - `U`: The outputs `Option`'s value type
+pub trait FallibleMapOptExt<T, U> {
+
+    /// Try to apply a map function that may return `None` to the option
+    fn try_map_opt<F>(self, f: F) -> Option<Option<U>> where
+        F: FnOnce(T) -> Option<U>;
+
+}
+
+impl<T, U> FallibleMapOptExt<T, U> for Option<T> {
+    fn try_map_opt<F>(self, f: F) -> Option<Option<U>> where
+        F: FnOnce(T) -> Option<U>
+    {
+        match self {
+            Some(x) => f(x).map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+/// Extend `Option<&mut T>` with a `try_update` method that applies a fallible
+/// mutation to the value, if any
+///
+/// This is `if let Some(t) = opt { f(t)? }` in expression position. The result
+/// tells whether there was a value to update; use `as_mut` to update the
+/// contents of a `&mut Option<T>`.
+///
+/// # Type parameters
+///
+/// - `T`: The type of the referenced value
+pub trait TryUpdateExt<T: ?Sized> {
+
+    /// Try to mutate the value, returning whether there was one
+    fn try_update<E, F>(self, f: F) -> Result<bool, E> where
+        F: FnOnce(&mut T) -> Result<(), E>;
+
+}
+
+impl<T: ?Sized> TryUpdateExt<T> for Option<&mut T> {
+    fn try_update<E, F>(self, f: F) -> Result<bool, E> where
+        F: FnOnce(&mut T) -> Result<(), E>
+    {
+        match self {
+            Some(t) => f(t).map(|()| true),
+            None => Ok(false),
+        }
+    }
+}
+
+/// Extend `Option<&str>`, `Option<String>` and other optional strings with a
+/// `try_parse` method
+///
+/// This is `try_map(|s| s.parse())`, the most common use of `try_map`, e.g. for
+/// optional query parameters and environment variables.
+pub trait TryParseExt {
+
+    /// Try to parse the string, if any
+    fn try_parse<T>(self) -> Result<Option<T>, T::Err> where
+        T: FromStr;
+
+}
+
+impl<S: AsRef<str>> TryParseExt for Option<S> {
+    fn try_parse<T>(self) -> Result<Option<T>, T::Err> where
+        T: FromStr
+    {
+        self.try_map(|s| s.as_ref().parse())
+    }
+}
+
 
 /// Extend `Option<Result<T>>` and Vec<Result<T>> with a `flip` method that scavenges the inner `Result`
 /// type and brings it to the outernmost type for easy error handling.
@@ -38,18 +357,46 @@ impl<T, U, E> FallibleMapExt<T, U, E> for Option<T> {
 /// This makes easy to `map`, `and_then` etc. with fallible (`Result`-returning)
 /// functions over `Option` and then call `flip` to "surface" the `Result` for error handling.
 ///
+/// This is the core trait of flipping: besides the impls in this crate, it can be
+/// implemented for your own containers. `Item` is the type of the values inside the
+/// `Result`s or `Option`s, and `Output` the flipped container, e.g. `T` and
+/// `Result<Vec<T>, E>` for `Vec<Result<T, E>>`.
+pub trait Flippable {
+    type Item;
+    type Output;
+
+    fn flip(self) -> Self::Output;
+}
+
+/// The former flipping trait, kept so that existing code continues to work
+///
+/// It's implemented for every `Flippable`, with `T` standing for `Item` and
+/// `ReturnType` for `Output`. Don't import it together with `Flippable`, as
+/// the two `flip` methods would be ambiguous.
+///
 /// # Type parameters
 ///
 /// - `T`: The inner value type
-/// - `E`: The error type of `Result`
+#[deprecated(since = "0.3.2", note = "use `Flippable` instead")]
 pub trait FlipResultExt<T> {
     type ReturnType;
 
     fn flip(self) -> Self::ReturnType;
 }
 
-impl<T, E> FlipResultExt<T> for Option<Result<T, E>> {
-    type ReturnType = Result<Option<T>, E>;
+#[allow(deprecated)]
+impl<C: Flippable> FlipResultExt<C::Item> for C {
+    type ReturnType = C::Output;
+
+    fn flip(self) -> C::Output
+    {
+        Flippable::flip(self)
+    }
+}
+
+impl<T, E> Flippable for Option<Result<T, E>> {
+    type Item = T;
+    type Output = Result<Option<T>, E>;
 
     fn flip(self) -> Result<Option<T>, E>
     {
@@ -60,40 +407,123 @@ impl<T, E> FlipResultExt<T> for Option<Result<T, E>> {
     }
 }
 
-impl<T, E> FlipResultExt<T> for Vec<Result<T, E>> {
-    type ReturnType = Result<Vec<T>, E>;
+/// Flipping a `Vec` that contains no errors can reuse its allocation when the
+/// layouts of `Result<T, E>` and `T` are compatible. With a zero-sized error
+/// type such as `()`, that's the case when `T` has a niche for the error to use,
+/// as references, `Box` and the `NonZero` integers do: `Result<&T, ()>` has the
+/// same layout as `&T`. Other types still need a tag, so `Result<u64, ()>` takes
+/// 16 bytes where `u64` takes 8.
+#[cfg(feature = "alloc")]
+impl<T, E> Flippable for Vec<Result<T, E>> {
+    type Item = T;
+    type Output = Result<Vec<T>, E>;
+    fn flip(mut self) -> Result<Vec<T>, E>
+    {
+        // Look for an error before allocating, so that failing early doesn't
+        // cost a buffer the size of the whole input
+        if let Some(i) = self.iter().position(Result::is_err) {
+            return match self.swap_remove(i) {
+                Err(e) => Err(e),
+                Ok(_) => unreachable!(),
+            };
+        }
+        // Collecting straight from `into_iter` lets the standard library reuse
+        // the allocation when the layouts of `Result<T, E>` and `T` are compatible
+        Ok(self.into_iter().map(|r| match r {
+            Ok(t) => t,
+            Err(_) => unreachable!(),
+        }).collect())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Flippable for Vec<Option<T>> {
+    type Item = T;
+    type Output = Option<Vec<T>>;
+    fn flip(self) -> Option<Vec<T>>
+    {
+        if self.iter().any(Option::is_none) {
+            return None;
+        }
+        Some(self.into_iter().map(|o| match o {
+            Some(t) => t,
+            None => unreachable!(),
+        }).collect())
+    }
+}
+
+/// Extend `Option<Result<Option<T>>>` with a `flip_flatten` method that flips and
+/// collapses the two layers of `Option` into one.
+///
+/// This shape comes up with optional lookups of optional fields, and
+/// `flip_flatten` spares the `.flip()?.flatten()` dance.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait FlipFlattenExt<T, E> {
+
+    /// Flip and flatten into `Result<Option<T>, E>`
+    fn flip_flatten(self) -> Result<Option<T>, E>;
+
+}
+
+impl<T, E> FlipFlattenExt<T, E> for Option<Result<Option<T>, E>> {
+    fn flip_flatten(self) -> Result<Option<T>, E>
+    {
+        match self {
+            Some(r) => r,
+            None => Ok(None),
+        }
+    }
+}
+
+/// Flipping a slice of `Copy` values copies them out without consuming the slice
+///
+/// This is a borrowing flip, for when the results are still needed afterwards;
+/// it's not a faster path for the owned `Vec` flip, which is unchanged. The
+/// slice is first scanned for an error, and only then are the values copied
+/// into a `Vec`. Only the values need to be `Copy`: the error, if any, is
+/// cloned once.
+#[cfg(feature = "alloc")]
+impl<T: Copy, E: Clone> Flippable for &[Result<T, E>] {
+    type Item = T;
+    type Output = Result<Vec<T>, E>;
     fn flip(self) -> Result<Vec<T>, E>
     {
-        let mut result_vec = Vec::with_capacity(self.len());
-        for t in self {
-            match t {
-                Ok(u) => result_vec.push(u),
-                Err(e) => return Err(e),
-            }
+        if let Some(Err(e)) = self.iter().find(|r| r.is_err()) {
+            return Err(e.clone());
         }
-        Ok(result_vec)
+        Ok(self.iter().map(|r| match *r {
+            Ok(t) => t,
+            Err(_) => unreachable!(),
+        }).collect())
     }
 }
 
-impl<T> FlipResultExt<T> for Vec<Option<T>> {
-    type ReturnType = Option<Vec<T>>;
+/// Flipping a slice of `Copy` values copies them out without consuming the slice
+#[cfg(feature = "alloc")]
+impl<T: Copy> Flippable for &[Option<T>] {
+    type Item = T;
+    type Output = Option<Vec<T>>;
     fn flip(self) -> Option<Vec<T>>
     {
-        let mut result_vec = Vec::with_capacity(self.len());
-        for t in self {
-            match t {
-                Some(u) => result_vec.push(u),
-                None => return None,
-            }
+        if self.iter().any(Option::is_none) {
+            return None;
         }
-        Some(result_vec)
+        Some(self.iter().map(|o| match *o {
+            Some(t) => t,
+            None => unreachable!(),
+        }).collect())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use FallibleMapExt;
-    use FlipResultExt;
+    use FallibleMapOptExt;
+    use Flippable;
 
     #[test]
     fn test_try_map_1() {
@@ -137,6 +567,72 @@ mod tests {
         assert_eq!(inner(), Err("oh noes"));
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_try_map_with() {
+        struct Parser {
+            parsed: usize,
+        }
+
+        impl Parser {
+            fn parse(&mut self, s: &str) -> Result<i32, String> {
+                self.parsed += 1;
+                s.parse().map_err(|_| format!("bad input {}", s))
+            }
+
+            fn parse_opt(&mut self, s: Option<&str>) -> Result<Option<i32>, String> {
+                s.try_map_with(self, |p, s| p.parse(s))
+            }
+        }
+
+        let mut p = Parser { parsed: 0 };
+        assert_eq!(p.parse_opt(Some("42")), Ok(Some(42)));
+        assert_eq!(p.parse_opt(None), Ok(None));
+        assert_eq!(p.parse_opt(Some("x")), Err("bad input x".to_string()));
+        assert_eq!(p.parsed, 2);
+    }
+
+    #[test]
+    fn test_try_map_opt_1() {
+        fn inner() -> Option<Option<u32>> {
+            let x = Some(42u32)
+                .try_map_opt(|x| x.checked_sub(1))?
+                .try_map_opt(|x| x.checked_sub(100))?
+                .try_map_opt(|x| x.checked_sub(1))?;
+
+            Some(x)
+        }
+        assert_eq!(inner(), None);
+    }
+
+    #[test]
+    fn test_try_map_opt_2() {
+        assert_eq!(Some(42u32).try_map_opt(|x| x.checked_sub(1)), Some(Some(41)));
+        assert_eq!(None.try_map_opt(|x: u32| x.checked_sub(1)), Some(None));
+    }
+
+    #[test]
+    fn test_try_update() {
+        use TryUpdateExt;
+
+        let mut x = Some(1);
+        assert_eq!(x.as_mut().try_update(|x| { *x += 1; Ok::<_, ()>(()) }), Ok(true));
+        assert_eq!(x, Some(2));
+        assert_eq!(x.as_mut().try_update(|_| Err("oh noes")), Err("oh noes"));
+        let mut y: Option<i32> = None;
+        assert_eq!(y.as_mut().try_update(|_| Err("oh noes")), Ok(false));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_try_parse() {
+        use TryParseExt;
+
+        assert_eq!(Some("42").try_parse::<i32>(), Ok(Some(42)));
+        assert_eq!(None::<String>.try_parse::<i32>(), Ok(None));
+        assert!(Some("x".to_string()).try_parse::<i32>().is_err());
+    }
+
     #[test]
     fn test_flip_1() {
         fn inner() -> Result<Option<i32>, &'static str> {
@@ -179,6 +675,46 @@ mod tests {
         assert_eq!(inner(), Err("oh noes"));
     }
 
+    #[test]
+    fn test_flippable_custom_container() {
+        #[derive(Debug, PartialEq)]
+        struct Pair<T>(T, T);
+
+        impl<T, E> Flippable for Pair<Result<T, E>> {
+            type Item = T;
+            type Output = Result<Pair<T>, E>;
+
+            fn flip(self) -> Result<Pair<T>, E> {
+                Ok(Pair(self.0?, self.1?))
+            }
+        }
+
+        assert_eq!(Pair(Ok::<_, ()>(1), Ok(2)).flip(), Ok(Pair(1, 2)));
+        assert_eq!(Pair(Ok(1), Err("oh noes")).flip(), Err("oh noes"));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_flip_result_ext_compat() {
+        fn surface<T, C: ::FlipResultExt<T>>(c: C) -> C::ReturnType {
+            ::FlipResultExt::flip(c)
+        }
+
+        assert_eq!(surface(Some(Ok::<_, ()>(42))), Ok(Some(42)));
+        assert_eq!(surface(Some(Err::<i32, _>("oh noes"))), Err("oh noes"));
+    }
+
+    #[test]
+    fn test_flip_flatten() {
+        use FlipFlattenExt;
+
+        assert_eq!(Some(Ok::<_, ()>(Some(42))).flip_flatten(), Ok(Some(42)));
+        assert_eq!(Some(Ok::<Option<i32>, ()>(None)).flip_flatten(), Ok(None));
+        assert_eq!(None::<Result<Option<i32>, ()>>.flip_flatten(), Ok(None));
+        assert_eq!(Some(Err::<Option<i32>, _>("oh noes")).flip_flatten(), Err("oh noes"));
+    }
+
+    #[cfg(feature = "std")]
     #[test]
     fn test_flip_vec_1() {
         fn inner() -> Result<Vec<i32>, &'static str> {
@@ -190,6 +726,7 @@ mod tests {
         assert_eq!(inner(), Ok(vec![43, 101, 100, 2, 43, 10001]));
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn test_flip_vec_2() {
         fn inner() -> Result<Vec<i32>, &'static str> {
@@ -200,4 +737,78 @@ mod tests {
         }
         assert_eq!(inner(), Err("heatenings"));
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_flip_slice_copy() {
+        let x: Vec<Result<u64, &'static str>> = vec![Ok(1), Ok(2), Ok(3)];
+        assert_eq!(x[..].flip(), Ok(vec![1, 2, 3]));
+        assert_eq!(x[..2].flip(), Ok(vec![1, 2]));
+        let x: Vec<Result<u64, &'static str>> = vec![Ok(1), Err("oh noes"), Err("oh foes")];
+        assert_eq!(x[..].flip(), Err("oh noes"));
+        assert_eq!([Some(1u8), Some(2)][..].flip(), Some(vec![1, 2]));
+        assert_eq!([Some(1u8), None][..].flip(), None);
+        let x: Vec<Result<u64, String>> = vec![Ok(1), Err("oh noes".to_string())];
+        assert_eq!(x[..].flip(), Err("oh noes".to_string()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_flip_vec_option() {
+        assert_eq!(vec![Some(1), Some(2), Some(3)].flip(), Some(vec![1, 2, 3]));
+        assert_eq!(vec![None, Some(2), None].flip(), None);
+        assert_eq!(Vec::<Option<i32>>::new().flip(), Some(vec![]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_flip_vec_first_error() {
+        let x: Vec<Result<i32, &'static str>> = vec![Ok(1), Err("oh noes"), Ok(3), Err("oh foes")];
+        assert_eq!(x.flip(), Err("oh noes"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_flip_vec_niche_layouts() {
+        use std::mem::size_of;
+
+        // The allocation is reused by the standard library's in-place collection
+        // of `vec::IntoIter`, which applies when the source and destination
+        // elements have the same size and alignment. That's an optimization
+        // rather than a documented guarantee, so a change would show up here.
+        assert_eq!(size_of::<Result<&i32, ()>>(), size_of::<&i32>());
+        let (a, b) = (1, 2);
+        let x: Vec<Result<&i32, ()>> = vec![Ok(&a), Ok(&b)];
+        let ptr = x.as_ptr() as usize;
+        let y = x.flip().unwrap();
+        assert_eq!(y, vec![&1, &2]);
+        assert_eq!(y.as_ptr() as usize, ptr);
+
+        assert_eq!(size_of::<Option<Box<i32>>>(), size_of::<Box<i32>>());
+        let x: Vec<Option<Box<i32>>> = vec![Some(Box::new(1)), Some(Box::new(2))];
+        let ptr = x.as_ptr() as usize;
+        let y = x.flip().unwrap();
+        assert_eq!(y, vec![Box::new(1), Box::new(2)]);
+        assert_eq!(y.as_ptr() as usize, ptr);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_flip_vec_zst_error() {
+        #[derive(Debug, PartialEq)]
+        struct Invalid;
+
+        // A zero-sized error fits in the niche of the `Box`, so the allocation is
+        // reused as in `test_flip_vec_niche_layouts`
+        assert_eq!(::std::mem::size_of::<Result<Box<i32>, Invalid>>(), ::std::mem::size_of::<Box<i32>>());
+        let x: Vec<Result<Box<i32>, Invalid>> = (0..100).map(|i| Ok(Box::new(i))).collect();
+        let ptr = x.as_ptr() as usize;
+        let y = x.flip().unwrap();
+        assert_eq!(y.len(), 100);
+        assert_eq!(y.as_ptr() as usize, ptr);
+        assert_eq!(*y[99], 99);
+
+        let x: Vec<Result<u8, ()>> = vec![Ok(1), Err(()), Ok(3)];
+        assert_eq!(x.flip(), Err(()));
+    }
 }
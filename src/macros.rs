@@ -0,0 +1,177 @@
+/// Map a fallible function over several `Option`s at once
+///
+/// `try_map!(a, b, c => f)` calls `f` with the contents of `a`, `b` and `c` if
+/// all of them are `Some`, and evaluates to `Result<Option<U>, E>`: `Ok(None)` if
+/// any of the inputs is `None`, otherwise the result of `f` wrapped in `Some`.
+/// The inputs are evaluated from left to right, stopping at the first `None`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate try_map;
+///
+/// # fn main() {
+/// let parse = |x: &str, y: &str| -> Result<i32, std::num::ParseIntError> { Ok(x.parse::<i32>()? + y.parse::<i32>()?) };
+/// assert_eq!(try_map!(Some("1"), Some("2") => parse), Ok(Some(3)));
+/// assert_eq!(try_map!(Some("1"), None => parse), Ok(None));
+/// assert!(try_map!(Some("1"), Some("x") => parse).is_err());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! try_map {
+    (@bind [$($bound:ident)*] $head:expr, $($rest:expr),+ => $f:expr) => {
+        match $head {
+            $crate::__private::Option::Some(x) => $crate::try_map!(@bind [$($bound)* x] $($rest),+ => $f),
+            $crate::__private::Option::None => $crate::__private::Result::Ok($crate::__private::Option::None),
+        }
+    };
+    (@bind [$($bound:ident)*] $head:expr => $f:expr) => {
+        match $head {
+            $crate::__private::Option::Some(x) => $crate::__private::Result::map(($f)($($bound,)* x), $crate::__private::Option::Some),
+            $crate::__private::Option::None => $crate::__private::Result::Ok($crate::__private::Option::None),
+        }
+    };
+    ($($opt:expr),+ => $f:expr) => {
+        $crate::try_map!(@bind [] $($opt),+ => $f)
+    };
+}
+
+/// Flip several `Result`s, or several `Option`s, into a single one of a tuple
+///
+/// `flip!(a, b, c)` evaluates to `Ok((a, b, c))` with the contents of the
+/// `Result`s if all of them are `Ok`, and to the first error otherwise.
+/// `flip!(Option: a, b, c)` does the same for `Option`s. The expressions are
+/// evaluated from left to right, stopping at the first failure, and any number
+/// of them is supported.
+///
+/// ```
+/// #[macro_use]
+/// extern crate try_map;
+///
+/// # fn main() {
+/// assert_eq!(flip!("1".parse::<i32>(), "2".parse::<u8>()), Ok((1, 2)));
+/// assert_eq!(flip!(Option: Some(1), None::<i32>), None);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! flip {
+    (@bind Result [$($bound:ident)*] $head:expr, $($rest:expr),+) => {
+        match $head {
+            $crate::__private::Result::Ok(x) => $crate::flip!(@bind Result [$($bound)* x] $($rest),+),
+            $crate::__private::Result::Err(e) => $crate::__private::Result::Err(e),
+        }
+    };
+    (@bind Result [$($bound:ident)*] $head:expr) => {
+        match $head {
+            $crate::__private::Result::Ok(x) => $crate::__private::Result::Ok(($($bound,)* x,)),
+            $crate::__private::Result::Err(e) => $crate::__private::Result::Err(e),
+        }
+    };
+    (@bind Option [$($bound:ident)*] $head:expr, $($rest:expr),+) => {
+        match $head {
+            $crate::__private::Option::Some(x) => $crate::flip!(@bind Option [$($bound)* x] $($rest),+),
+            $crate::__private::Option::None => $crate::__private::Option::None,
+        }
+    };
+    (@bind Option [$($bound:ident)*] $head:expr) => {
+        match $head {
+            $crate::__private::Option::Some(x) => $crate::__private::Option::Some(($($bound,)* x,)),
+            $crate::__private::Option::None => $crate::__private::Option::None,
+        }
+    };
+    (Option: $($e:expr),+ $(,)*) => {
+        $crate::flip!(@bind Option [] $($e),+)
+    };
+    ($($e:expr),+ $(,)*) => {
+        $crate::flip!(@bind Result [] $($e),+)
+    };
+}
+
+/// Chain several fallible maps over a container without a `?` between every stage
+///
+/// `pipeline!(input => f => g)` applies `f` and then `g` to every element of
+/// `input` with `try_fmap`, and evaluates to the `Result` of the last stage. It
+/// works with every `TryFunctor` container, such as `Option`, arrays and `Vec`. All the
+/// stages must have the same error type, and the pipeline stops at the first error.
+///
+/// ```
+/// #[macro_use]
+/// extern crate try_map;
+///
+/// # fn main() {
+/// let parse = |s: &str| s.parse::<u8>().map_err(|_| format!("{} is not a number", s));
+/// let double = |x: u8| x.checked_mul(2).ok_or(format!("{} is too big", x));
+/// assert_eq!(pipeline!(["1", "2"] => parse => double), Ok([2, 4]));
+/// assert_eq!(pipeline!(["1", "200"] => parse => double), Err("200 is too big".to_string()));
+/// assert_eq!(pipeline!(Some("1") => parse => double), Ok(Some(2)));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! pipeline {
+    (@chain $acc:expr => $f:expr $(=> $rest:expr)*) => {
+        $crate::pipeline!(@chain $crate::__private::Result::and_then($acc, |c| $crate::TryFunctor::try_fmap(c, $f)) $(=> $rest)*)
+    };
+    (@chain $acc:expr) => { $acc };
+    ($input:expr $(=> $f:expr)+) => {
+        $crate::pipeline!(@chain $crate::__private::Result::Ok($input) $(=> $f)+)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_try_map_macro() {
+        let checked_sum = |x: u8, y: u8, z: u8| x.checked_add(y).and_then(|s| s.checked_add(z)).ok_or("overflow");
+        assert_eq!(try_map!(Some(1), Some(2), Some(3) => checked_sum), Ok(Some(6)));
+        assert_eq!(try_map!(Some(1), None, Some(3) => checked_sum), Ok(None));
+        assert_eq!(try_map!(Some(1), Some(255), Some(3) => checked_sum), Err("overflow"));
+        assert_eq!(try_map!(Some(1) => |x: u8| x.checked_sub(2).ok_or("underflow")), Err("underflow"));
+    }
+
+    #[test]
+    fn test_try_map_macro_lazy() {
+        let mut evaluated = false;
+        let r = try_map!(None::<i32>, { evaluated = true; Some(1) } => |x, y| Ok::<_, ()>(x + y));
+        assert_eq!(r, Ok(None));
+        assert!(!evaluated);
+    }
+
+    #[test]
+    fn test_flip_macro() {
+        let x: Result<_, &'static str> = flip!(Ok(1), Ok("two"), Ok(3.0));
+        assert_eq!(x, Ok((1, "two", 3.0)));
+        let x: Result<(i32, i32, i32), _> = flip!(Ok(1), Err("oh noes"), Err("oh foes"));
+        assert_eq!(x, Err("oh noes"));
+        assert_eq!(flip!(Option: Some(1), Some('2')), Some((1, '2')));
+        assert_eq!(flip!(Option: Some(1), None::<char>), None);
+    }
+
+    #[test]
+    fn test_flip_macro_short_circuit() {
+        let mut evaluated = false;
+        let x: Result<(i32, i32), _> = flip!(Err("oh noes"), { evaluated = true; Ok(2) });
+        assert_eq!(x, Err("oh noes"));
+        assert!(!evaluated);
+        assert_eq!(flip!(Ok::<_, ()>(1)), Ok((1,)));
+    }
+
+    #[test]
+    fn test_pipeline_macro() {
+        let x = pipeline!(Some(42)
+            => |x| Ok(x + 1)
+            => |x| Ok(x + 1)
+            => |x| if true { Err("oh noes") } else { Ok(x + 1) }
+            => |x| Ok(x + 1));
+        assert_eq!(x, Err("oh noes"));
+        let x: Result<_, &'static str> = pipeline!(Some(42) => |x| Ok(x + 1) => |x| Ok(x * 2));
+        assert_eq!(x, Ok(Some(86)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_pipeline_macro_vec() {
+        let parse = |s: &str| s.parse::<i32>().map_err(|_| s.to_string());
+        let positive = |x: i32| if x > 0 { Ok(x) } else { Err(format!("{} is not positive", x)) };
+        assert_eq!(pipeline!(vec!["1", "2"] => parse => positive), Ok(vec![1, 2]));
+        assert_eq!(pipeline!(vec!["1", "-2"] => parse => positive), Err("-2 is not positive".to_string()));
+    }
+}
@@ -0,0 +1,623 @@
+use alloc::collections::{btree_map, BTreeMap};
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+#[cfg(feature = "std")]
+use std::collections::{hash_map, HashMap};
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+use {MapKeysError, ZipByKeyError};
+
+/// Extend `HashMap` and `BTreeMap` with a `try_map_entries` method that converts
+/// both the keys and the values with a fallible function.
+///
+/// If several entries are converted into the same key, the entry converted last
+/// is kept. For `BTreeMap` that's the one with the greatest original key; the
+/// iteration order of `HashMap` is unspecified, so the key conversion should be
+/// injective if this matters. The mapping stops at the first error.
+///
+/// # Type parameters
+///
+/// - `K`: The input map's key type
+/// - `V`: The input map's value type
+pub trait FallibleMapEntriesExt<K, V> {
+    /// The same kind of map with keys of type `K2` and values of type `V2`
+    type Mapped<K2, V2>;
+
+    /// Apply a fallible map function to every entry, stopping at the first error
+    fn try_map_entries<K2, V2, E, F>(self, f: F) -> Result<Self::Mapped<K2, V2>, E> where
+        Self::Mapped<K2, V2>: FromIterator<(K2, V2)>,
+        F: FnMut(K, V) -> Result<(K2, V2), E>;
+}
+
+impl<K, V> FallibleMapEntriesExt<K, V> for BTreeMap<K, V> {
+    type Mapped<K2, V2> = BTreeMap<K2, V2>;
+
+    fn try_map_entries<K2, V2, E, F>(self, mut f: F) -> Result<BTreeMap<K2, V2>, E> where
+        BTreeMap<K2, V2>: FromIterator<(K2, V2)>,
+        F: FnMut(K, V) -> Result<(K2, V2), E>
+    {
+        self.into_iter().map(|(k, v)| f(k, v)).collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S: BuildHasher> FallibleMapEntriesExt<K, V> for HashMap<K, V, S> {
+    type Mapped<K2, V2> = HashMap<K2, V2, S>;
+
+    fn try_map_entries<K2, V2, E, F>(self, mut f: F) -> Result<HashMap<K2, V2, S>, E> where
+        HashMap<K2, V2, S>: FromIterator<(K2, V2)>,
+        F: FnMut(K, V) -> Result<(K2, V2), E>
+    {
+        self.into_iter().map(|(k, v)| f(k, v)).collect()
+    }
+}
+
+/// Extend the `Entry` types of `HashMap` and `BTreeMap` with an
+/// `or_try_insert_with` method, whose initializer is fallible.
+///
+/// If the entry is vacant and the initializer fails, nothing is inserted and the
+/// error is returned.
+///
+/// # Type parameters
+///
+/// - `V`: The value type of the map
+pub trait TryEntryExt<'a, V> {
+
+    /// Return the value of the entry, inserting the result of `f` first if the entry is vacant
+    fn or_try_insert_with<E, F>(self, f: F) -> Result<&'a mut V, E> where
+        F: FnOnce() -> Result<V, E>;
+
+}
+
+impl<'a, K: Ord, V> TryEntryExt<'a, V> for btree_map::Entry<'a, K, V> {
+    fn or_try_insert_with<E, F>(self, f: F) -> Result<&'a mut V, E> where
+        F: FnOnce() -> Result<V, E>
+    {
+        match self {
+            btree_map::Entry::Occupied(o) => Ok(o.into_mut()),
+            btree_map::Entry::Vacant(v) => Ok(v.insert(f()?)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, K, V> TryEntryExt<'a, V> for hash_map::Entry<'a, K, V> {
+    fn or_try_insert_with<E, F>(self, f: F) -> Result<&'a mut V, E> where
+        F: FnOnce() -> Result<V, E>
+    {
+        match self {
+            hash_map::Entry::Occupied(o) => Ok(o.into_mut()),
+            hash_map::Entry::Vacant(v) => Ok(v.insert(f()?)),
+        }
+    }
+}
+
+/// Extend `HashMap` and `BTreeMap` with methods that mutate the values in place
+/// with a fallible function.
+///
+/// Like `try_map_mut` for slices, `try_map_values_mut` leaves the values
+/// preceding an error mutated, while `try_map_values_mut_or_undo` rolls them back
+/// with an undo function.
+///
+/// # Type parameters
+///
+/// - `V`: The value type of the map
+pub trait FallibleMapValuesMutExt<V> {
+
+    /// Mutate every value, stopping at the first error and keeping the earlier mutations
+    fn try_map_values_mut<E, F>(&mut self, f: F) -> Result<(), E> where
+        F: FnMut(&mut V) -> Result<(), E>;
+
+    /// Mutate every value, stopping at the first error and undoing the earlier mutations
+    ///
+    /// On error, `undo` is called on the values that were successfully mutated,
+    /// in the reverse iteration order of the map, like `try_map_mut_or_undo`
+    /// does. The value for which `f` failed isn't passed to `undo`.
+    fn try_map_values_mut_or_undo<E, F, G>(&mut self, f: F, undo: G) -> Result<(), E> where
+        F: FnMut(&mut V) -> Result<(), E>,
+        G: FnMut(&mut V);
+
+}
+
+impl<K, V> FallibleMapValuesMutExt<V> for BTreeMap<K, V> {
+    fn try_map_values_mut<E, F>(&mut self, f: F) -> Result<(), E> where
+        F: FnMut(&mut V) -> Result<(), E>
+    {
+        self.values_mut().try_for_each(f)
+    }
+
+    fn try_map_values_mut_or_undo<E, F, G>(&mut self, mut f: F, undo: G) -> Result<(), E> where
+        F: FnMut(&mut V) -> Result<(), E>,
+        G: FnMut(&mut V)
+    {
+        for (i, v) in self.values_mut().enumerate() {
+            if let Err(e) = f(v) {
+                self.values_mut().take(i).rev().for_each(undo);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> FallibleMapValuesMutExt<V> for HashMap<K, V, S> {
+    fn try_map_values_mut<E, F>(&mut self, f: F) -> Result<(), E> where
+        F: FnMut(&mut V) -> Result<(), E>
+    {
+        self.values_mut().try_for_each(f)
+    }
+
+    fn try_map_values_mut_or_undo<E, F, G>(&mut self, mut f: F, undo: G) -> Result<(), E> where
+        F: FnMut(&mut V) -> Result<(), E>,
+        G: FnMut(&mut V)
+    {
+        // The iteration order doesn't change as long as the map isn't modified,
+        // but it can't be reversed, so the values to undo are collected first
+        for (i, v) in self.values_mut().enumerate() {
+            if let Err(e) = f(v) {
+                let mutated: Vec<&mut V> = self.values_mut().take(i).collect();
+                mutated.into_iter().rev().for_each(undo);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What `try_zip_map_by_key` does with the keys that are present in only one of the maps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnmatchedKeys {
+    /// Fail with `ZipByKeyError::OnlyInLeft` or `ZipByKeyError::OnlyInRight`
+    Error,
+    /// Leave the entry out of the output
+    Skip,
+}
+
+/// Extend `HashMap` and `BTreeMap` with methods that join two maps by key,
+/// combining the values with a fallible function.
+///
+/// `try_zip_map_by_key` combines the values of the keys present in both maps,
+/// and fails or skips the other keys according to `UnmatchedKeys`.
+/// `try_zip_map_by_key_outer` passes absent values as `None` instead, so the
+/// function handles every key. The keys of `self` are visited first, then the
+/// remaining keys of `other`; the combination stops at the first error.
+///
+/// # Type parameters
+///
+/// - `K`: The key type
+/// - `V1`: The value type of `self`
+pub trait FallibleZipMapByKeyExt<K, V1> {
+    /// The same kind of map with values of type `V2`
+    type Mapped<V2>;
+
+    /// Combine the values of the keys present in both maps with a fallible function
+    fn try_zip_map_by_key<V2, V3, E, F>(self, other: Self::Mapped<V2>, unmatched: UnmatchedKeys, f: F) -> Result<Self::Mapped<V3>, ZipByKeyError<K, E>> where
+        F: FnMut(&K, V1, V2) -> Result<V3, E>;
+
+    /// Combine the values of every key present in either map with a fallible function
+    fn try_zip_map_by_key_outer<V2, V3, E, F>(self, other: Self::Mapped<V2>, f: F) -> Result<Self::Mapped<V3>, E> where
+        F: FnMut(&K, Option<V1>, Option<V2>) -> Result<V3, E>;
+}
+
+impl<K: Ord, V1> FallibleZipMapByKeyExt<K, V1> for BTreeMap<K, V1> {
+    type Mapped<V2> = BTreeMap<K, V2>;
+
+    fn try_zip_map_by_key<V2, V3, E, F>(self, mut other: BTreeMap<K, V2>, unmatched: UnmatchedKeys, mut f: F) -> Result<BTreeMap<K, V3>, ZipByKeyError<K, E>> where
+        F: FnMut(&K, V1, V2) -> Result<V3, E>
+    {
+        let mut result = BTreeMap::new();
+        for (k, v1) in self {
+            match other.remove(&k) {
+                Some(v2) => {
+                    let v3 = f(&k, v1, v2).map_err(ZipByKeyError::Map)?;
+                    result.insert(k, v3);
+                },
+                None if unmatched == UnmatchedKeys::Error => return Err(ZipByKeyError::OnlyInLeft(k)),
+                None => (),
+            }
+        }
+        match other.into_iter().next() {
+            Some((k, _)) if unmatched == UnmatchedKeys::Error => Err(ZipByKeyError::OnlyInRight(k)),
+            _ => Ok(result),
+        }
+    }
+
+    fn try_zip_map_by_key_outer<V2, V3, E, F>(self, mut other: BTreeMap<K, V2>, mut f: F) -> Result<BTreeMap<K, V3>, E> where
+        F: FnMut(&K, Option<V1>, Option<V2>) -> Result<V3, E>
+    {
+        let mut result = BTreeMap::new();
+        for (k, v1) in self {
+            let v3 = f(&k, Some(v1), other.remove(&k))?;
+            result.insert(k, v3);
+        }
+        for (k, v2) in other {
+            let v3 = f(&k, None, Some(v2))?;
+            result.insert(k, v3);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V1, S: BuildHasher + Default> FallibleZipMapByKeyExt<K, V1> for HashMap<K, V1, S> {
+    type Mapped<V2> = HashMap<K, V2, S>;
+
+    fn try_zip_map_by_key<V2, V3, E, F>(self, mut other: HashMap<K, V2, S>, unmatched: UnmatchedKeys, mut f: F) -> Result<HashMap<K, V3, S>, ZipByKeyError<K, E>> where
+        F: FnMut(&K, V1, V2) -> Result<V3, E>
+    {
+        let mut result = HashMap::with_hasher(S::default());
+        for (k, v1) in self {
+            match other.remove(&k) {
+                Some(v2) => {
+                    let v3 = f(&k, v1, v2).map_err(ZipByKeyError::Map)?;
+                    result.insert(k, v3);
+                },
+                None if unmatched == UnmatchedKeys::Error => return Err(ZipByKeyError::OnlyInLeft(k)),
+                None => (),
+            }
+        }
+        match other.into_iter().next() {
+            Some((k, _)) if unmatched == UnmatchedKeys::Error => Err(ZipByKeyError::OnlyInRight(k)),
+            _ => Ok(result),
+        }
+    }
+
+    fn try_zip_map_by_key_outer<V2, V3, E, F>(self, mut other: HashMap<K, V2, S>, mut f: F) -> Result<HashMap<K, V3, S>, E> where
+        F: FnMut(&K, Option<V1>, Option<V2>) -> Result<V3, E>
+    {
+        let mut result = HashMap::with_hasher(S::default());
+        for (k, v1) in self {
+            let v3 = f(&k, Some(v1), other.remove(&k))?;
+            result.insert(k, v3);
+        }
+        for (k, v2) in other {
+            let v3 = f(&k, None, Some(v2))?;
+            result.insert(k, v3);
+        }
+        Ok(result)
+    }
+}
+
+/// Extend `HashMap` and `BTreeMap` with a `try_merge` method that merges another
+/// map into this one, resolving the conflicts with a fallible function.
+///
+/// The resolver is only called for the keys present in both maps, with the
+/// value of `self` first; e.g. for layered configuration files, where two
+/// settings for the same key may be incompatible. The merge stops at the first
+/// error.
+///
+/// # Type parameters
+///
+/// - `K`: The key type
+/// - `V`: The value type
+pub trait TryMergeExt<K, V>: Sized {
+
+    /// Merge `other` into `self`, resolving the conflicting keys with a fallible function
+    fn try_merge<E, F>(self, other: Self, f: F) -> Result<Self, E> where
+        F: FnMut(&K, V, V) -> Result<V, E>;
+
+}
+
+impl<K: Ord, V> TryMergeExt<K, V> for BTreeMap<K, V> {
+    fn try_merge<E, F>(mut self, other: Self, mut f: F) -> Result<Self, E> where
+        F: FnMut(&K, V, V) -> Result<V, E>
+    {
+        for (k, v2) in other {
+            match self.entry(k) {
+                btree_map::Entry::Occupied(o) => {
+                    let (k, v1) = o.remove_entry();
+                    let v = f(&k, v1, v2)?;
+                    self.insert(k, v);
+                },
+                btree_map::Entry::Vacant(v) => { v.insert(v2); },
+            }
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, S: BuildHasher> TryMergeExt<K, V> for HashMap<K, V, S> {
+    fn try_merge<E, F>(mut self, other: Self, mut f: F) -> Result<Self, E> where
+        F: FnMut(&K, V, V) -> Result<V, E>
+    {
+        for (k, v2) in other {
+            match self.entry(k) {
+                hash_map::Entry::Occupied(o) => {
+                    let (k, v1) = o.remove_entry();
+                    let v = f(&k, v1, v2)?;
+                    self.insert(k, v);
+                },
+                hash_map::Entry::Vacant(v) => { v.insert(v2); },
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Extend `BTreeMap` with methods that rebuild the map with fallibly converted keys.
+///
+/// `try_map_keys` keeps the last entry if several keys are converted into the
+/// same key, like `try_map_entries`. `try_map_keys_checked` verifies instead that
+/// the conversion preserves the order of the keys, which also rules out
+/// duplicates: a key migration that silently reorders or merges entries is
+/// reported as a `MapKeysError` rather than caught downstream. Both stop at the
+/// first error.
+///
+/// # Type parameters
+///
+/// - `K`: The input map's key type
+/// - `V`: The value type
+pub trait FallibleMapKeysExt<K, V> {
+
+    /// Apply a fallible map function to every key, stopping at the first error
+    fn try_map_keys<K2, E, F>(self, f: F) -> Result<BTreeMap<K2, V>, E> where
+        K2: Ord,
+        F: FnMut(K) -> Result<K2, E>;
+
+    /// Apply a fallible map function to every key, failing if the order of the keys changes
+    fn try_map_keys_checked<K2, E, F>(self, f: F) -> Result<BTreeMap<K2, V>, MapKeysError<K2, E>> where
+        K2: Ord,
+        F: FnMut(K) -> Result<K2, E>;
+
+}
+
+impl<K, V> FallibleMapKeysExt<K, V> for BTreeMap<K, V> {
+    fn try_map_keys<K2, E, F>(self, mut f: F) -> Result<BTreeMap<K2, V>, E> where
+        K2: Ord,
+        F: FnMut(K) -> Result<K2, E>
+    {
+        self.into_iter().map(|(k, v)| f(k).map(|k2| (k2, v))).collect()
+    }
+
+    fn try_map_keys_checked<K2, E, F>(self, mut f: F) -> Result<BTreeMap<K2, V>, MapKeysError<K2, E>> where
+        K2: Ord,
+        F: FnMut(K) -> Result<K2, E>
+    {
+        let mut result = BTreeMap::new();
+        for (k, v) in self {
+            let k2 = f(k).map_err(MapKeysError::Map)?;
+            // The keys come in increasing order, so each converted key must be greater than the last one.
+            match result.last_key_value() {
+                Some((last, _)) if k2 == *last => return Err(MapKeysError::Duplicate(k2)),
+                Some((last, _)) if k2 < *last => return Err(MapKeysError::OutOfOrder(k2)),
+                _ => { result.insert(k2, v); },
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Extend `Vec` with methods that group the elements by a fallibly computed key.
+///
+/// This is grouping by a parsed key that might be malformed, without an explicit
+/// loop over `entry`. Within each group, the elements keep their original order.
+/// The grouping stops at the first error.
+///
+/// # Type parameters
+///
+/// - `T`: The element type
+pub trait FallibleGroupMapExt<T> {
+
+    /// Group the elements into a `HashMap` by a fallible key function
+    #[cfg(feature = "std")]
+    fn try_group_map<K, E, F>(self, f: F) -> Result<HashMap<K, Vec<T>>, E> where
+        K: Eq + Hash,
+        F: FnMut(&T) -> Result<K, E>;
+
+    /// Group the elements into a `BTreeMap` by a fallible key function
+    fn try_group_map_btree<K, E, F>(self, f: F) -> Result<BTreeMap<K, Vec<T>>, E> where
+        K: Ord,
+        F: FnMut(&T) -> Result<K, E>;
+
+}
+
+impl<T> FallibleGroupMapExt<T> for Vec<T> {
+    #[cfg(feature = "std")]
+    fn try_group_map<K, E, F>(self, mut f: F) -> Result<HashMap<K, Vec<T>>, E> where
+        K: Eq + Hash,
+        F: FnMut(&T) -> Result<K, E>
+    {
+        let mut groups = HashMap::new();
+        for t in self {
+            groups.entry(f(&t)?).or_insert_with(Vec::new).push(t);
+        }
+        Ok(groups)
+    }
+
+    fn try_group_map_btree<K, E, F>(self, mut f: F) -> Result<BTreeMap<K, Vec<T>>, E> where
+        K: Ord,
+        F: FnMut(&T) -> Result<K, E>
+    {
+        let mut groups = BTreeMap::new();
+        for t in self {
+            groups.entry(f(&t)?).or_insert_with(Vec::new).push(t);
+        }
+        Ok(groups)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use test_util::increment;
+    use FallibleGroupMapExt;
+    use FallibleMapEntriesExt;
+    use FallibleMapKeysExt;
+    use FallibleMapValuesMutExt;
+    use FallibleZipMapByKeyExt;
+    use TryEntryExt;
+    use TryMergeExt;
+    use {MapKeysError, UnmatchedKeys, ZipByKeyError};
+
+    #[test]
+    fn test_try_map_entries_hash_map() {
+        let mut m = HashMap::new();
+        m.insert("1", "one");
+        m.insert("2", "two");
+        let x = m.clone().try_map_entries(|k, v| k.parse::<i32>().map(|k| (k, v.len())));
+        assert_eq!(x.unwrap(), vec![(1, 3), (2, 3)].into_iter().collect::<HashMap<_, _>>());
+        m.insert("x", "ex");
+        assert!(m.try_map_entries(|k, v| k.parse::<i32>().map(|k| (k, v))).is_err());
+    }
+
+    #[test]
+    fn test_try_map_entries_duplicate_keys() {
+        let m: BTreeMap<i32, &'static str> = vec![(1, "a"), (2, "b"), (3, "c")].into_iter().collect();
+        let x = m.try_map_entries(|k, v| Ok::<_, ()>((k % 2, v)));
+        assert_eq!(x, Ok(vec![(0, "b"), (1, "c")].into_iter().collect()));
+    }
+
+    #[test]
+    fn test_or_try_insert_with_hash_map() {
+        let mut cache = HashMap::new();
+        assert_eq!(cache.entry("42").or_try_insert_with(|| "42".parse::<i32>()).copied(), Ok(42));
+        assert!(cache.entry("x").or_try_insert_with(|| "x".parse::<i32>()).is_err());
+        assert_eq!(cache.entry("42").or_try_insert_with(|| Err(())).copied(), Ok(42));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_or_try_insert_with_btree_map() {
+        let mut m = BTreeMap::new();
+        *m.entry(1).or_try_insert_with(|| Ok::<_, ()>(10)).unwrap() += 1;
+        assert_eq!(m.entry(2).or_try_insert_with(|| Err("oh noes")), Err("oh noes"));
+        assert_eq!(m, vec![(1, 11)].into_iter().collect());
+    }
+
+    #[test]
+    fn test_try_map_values_mut() {
+        let mut m: BTreeMap<_, u8> = vec![(1, 1), (2, 255), (3, 3)].into_iter().collect();
+        assert_eq!(m.try_map_values_mut(increment), Err(255));
+        assert_eq!(m, vec![(1, 2), (2, 255), (3, 3)].into_iter().collect());
+    }
+
+    #[test]
+    fn test_try_map_values_mut_or_undo() {
+        let mut m: HashMap<_, u8> = vec![(1, 1), (2, 2), (3, 255), (4, 4)].into_iter().collect();
+        let before = m.clone();
+        assert_eq!(m.try_map_values_mut_or_undo(increment, |x| *x -= 1), Err(255));
+        assert_eq!(m, before);
+        m.remove(&3);
+        assert_eq!(m.try_map_values_mut_or_undo(increment, |x| *x -= 1), Ok(()));
+        assert_eq!(m, vec![(1, 2), (2, 3), (4, 5)].into_iter().collect());
+    }
+
+    #[test]
+    fn test_try_map_values_mut_or_undo_order() {
+        let mut undone = Vec::new();
+        let mut m: BTreeMap<_, u8> = vec![(1, 1), (2, 2), (3, 3), (4, 255)].into_iter().collect();
+        assert_eq!(m.try_map_values_mut_or_undo(increment, |x| { undone.push(*x); *x -= 1 }), Err(255));
+        assert_eq!(undone, vec![4, 3, 2]);
+
+        let mut undone = Vec::new();
+        let mut m: HashMap<_, u8> = (0..20).map(|i| (i, if i == 10 { 255 } else { i })).collect();
+        let mut expected: Vec<u8> = m.values().take_while(|&&x| x != 255).map(|x| x + 1).collect();
+        expected.reverse();
+        assert_eq!(m.try_map_values_mut_or_undo(increment, |x| { undone.push(*x); *x -= 1 }), Err(255));
+        assert_eq!(undone, expected);
+    }
+
+    fn stock() -> (BTreeMap<u32, &'static str>, BTreeMap<u32, u32>) {
+        let names = vec![(1, "apple"), (2, "pear"), (3, "plum")].into_iter().collect();
+        let counts = vec![(1, 10), (3, 0), (4, 7)].into_iter().collect();
+        (names, counts)
+    }
+
+    fn describe(_: &u32, name: &'static str, count: u32) -> Result<String, String> {
+        if count > 0 { Ok(format!("{} {}s", count, name)) } else { Err(format!("no {}s", name)) }
+    }
+
+    #[test]
+    fn test_try_zip_map_by_key() {
+        let (names, counts) = stock();
+        assert_eq!(names.try_zip_map_by_key(counts, UnmatchedKeys::Error, describe), Err(ZipByKeyError::OnlyInLeft(2)));
+        let (mut names, mut counts) = stock();
+        names.remove(&2);
+        counts.insert(3, 5);
+        assert_eq!(names.try_zip_map_by_key(counts, UnmatchedKeys::Error, describe), Err(ZipByKeyError::OnlyInRight(4)));
+        let (names, mut counts) = stock();
+        counts.insert(3, 5);
+        let x = names.try_zip_map_by_key(counts, UnmatchedKeys::Skip, describe);
+        assert_eq!(x, Ok(vec![(1, "10 apples".to_string()), (3, "5 plums".to_string())].into_iter().collect()));
+        let (names, counts) = stock();
+        assert_eq!(names.try_zip_map_by_key(counts, UnmatchedKeys::Skip, describe), Err(ZipByKeyError::Map("no plums".to_string())));
+    }
+
+    #[test]
+    fn test_try_zip_map_by_key_outer() {
+        let names: HashMap<u32, &str> = vec![(1, "apple"), (2, "pear")].into_iter().collect();
+        let counts: HashMap<u32, u32> = vec![(1, 10), (3, 7)].into_iter().collect();
+        let x = names.try_zip_map_by_key_outer(counts, |k, name, count| match (name, count) {
+            (Some(name), count) => Ok((name, count.unwrap_or(0))),
+            (None, _) => Err(*k),
+        });
+        assert_eq!(x, Err(3));
+    }
+
+    #[test]
+    fn test_try_merge() {
+        let base: HashMap<&str, u32> = vec![("port", 80), ("workers", 4)].into_iter().collect();
+        let local: HashMap<&str, u32> = vec![("port", 8080), ("timeout", 30)].into_iter().collect();
+        let merged = base.clone().try_merge(local, |_, _, v2| Ok::<_, ()>(v2));
+        assert_eq!(merged, Ok(vec![("port", 8080), ("workers", 4), ("timeout", 30)].into_iter().collect()));
+        let other: HashMap<&str, u32> = vec![("workers", 8)].into_iter().collect();
+        assert_eq!(base.try_merge(other, |k, v1, v2| if v1 == v2 { Ok(v1) } else { Err(*k) }), Err("workers"));
+    }
+
+    #[test]
+    fn test_try_merge_btree_map() {
+        let mut calls = 0;
+        let a: BTreeMap<i32, i32> = vec![(1, 1), (2, 2)].into_iter().collect();
+        let b: BTreeMap<i32, i32> = vec![(2, 20), (3, 30)].into_iter().collect();
+        let merged = a.try_merge(b, |_, v1, v2| {
+            calls += 1;
+            v1.checked_add(v2).ok_or("overflow")
+        });
+        assert_eq!(merged, Ok(vec![(1, 1), (2, 22), (3, 30)].into_iter().collect()));
+        assert_eq!(calls, 1);
+    }
+
+    fn date_key(line: &&'static str) -> Result<u32, String> {
+        line.split(':').next().unwrap().parse().map_err(|_| format!("malformed line: {}", line))
+    }
+
+    #[test]
+    fn test_try_group_map() {
+        let lines = vec!["1: a", "2: b", "1: c"];
+        let groups = lines.clone().try_group_map(date_key).unwrap();
+        assert_eq!(groups[&1], vec!["1: a", "1: c"]);
+        assert_eq!(groups[&2], vec!["2: b"]);
+        let groups = lines.try_group_map_btree(date_key).unwrap();
+        assert_eq!(groups.into_iter().collect::<Vec<_>>(), vec![(1, vec!["1: a", "1: c"]), (2, vec!["2: b"])]);
+    }
+
+    #[test]
+    fn test_try_group_map_error() {
+        let lines = vec!["1: a", "x: b", "y: c"];
+        assert_eq!(lines.clone().try_group_map(date_key), Err("malformed line: x: b".to_string()));
+        assert_eq!(lines.try_group_map_btree(date_key), Err("malformed line: x: b".to_string()));
+    }
+
+    #[test]
+    fn test_try_map_keys() {
+        let m: BTreeMap<&str, i32> = vec![("1", 1), ("01", 2), ("10", 3)].into_iter().collect();
+        let x = m.clone().try_map_keys(|k| k.parse::<u32>());
+        assert_eq!(x, Ok(vec![(1, 1), (10, 3)].into_iter().collect()));
+        assert_eq!(m.try_map_keys_checked(|k| k.parse::<u32>()), Err(MapKeysError::Duplicate(1)));
+    }
+
+    #[test]
+    fn test_try_map_keys_checked() {
+        let m: BTreeMap<&str, i32> = vec![("1", 1), ("2", 2), ("10", 3)].into_iter().collect();
+        assert_eq!(m.clone().try_map_keys_checked(|k| k.parse::<u32>()), Err(MapKeysError::OutOfOrder(2)));
+        let x = m.clone().try_map_keys_checked(|k| Ok::<_, ()>(k.len()));
+        assert_eq!(x, Err(MapKeysError::OutOfOrder(1)));
+        let x = m.clone().try_map_keys_checked(|k| Ok::<_, ()>(format!("key{}", k)));
+        assert_eq!(x.map(|m| m.len()), Ok(3));
+        assert!(matches!(m.try_map_keys_checked(|k| k.parse::<u8>().and_then(|_| "x".parse::<u8>())), Err(MapKeysError::Map(_))));
+    }
+}
@@ -0,0 +1,113 @@
+//! `try_map` and `flip` generalized over the `Try` trait, enabled by the `nightly` feature
+//!
+//! The closure can return any `Try` carrier, such as `Result`, `Option` or
+//! `ControlFlow`, and the carrier of the output is derived from it. This
+//! requires a nightly compiler, as `try_trait_v2` is unstable.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::ops::{ControlFlow, FromResidual, Residual, Try};
+
+/// Extend `Option` with a `try_map_any` method that works like `try_map`,
+/// but with a closure returning any `Try` carrier.
+///
+/// With a closure returning `Result<U, E>`, the result is `Result<Option<U>, E>`;
+/// with a closure returning `Option<U>`, it's `Option<Option<U>>`, and so on.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Option`'s value type
+pub trait TryMapAnyExt<T> {
+
+    /// Try to apply a map function returning any `Try` carrier to the option
+    fn try_map_any<F, R>(self, f: F) -> <R::Residual as Residual<Option<R::Output>>>::TryType where
+        F: FnOnce(T) -> R,
+        R: Try,
+        R::Residual: Residual<Option<R::Output>>;
+
+}
+
+impl<T> TryMapAnyExt<T> for Option<T> {
+    fn try_map_any<F, R>(self, f: F) -> <R::Residual as Residual<Option<R::Output>>>::TryType where
+        F: FnOnce(T) -> R,
+        R: Try,
+        R::Residual: Residual<Option<R::Output>>
+    {
+        self.map(f).flip_any()
+    }
+}
+
+/// Extend `Option<R>` and `Vec<R>`, where `R` is a `Try` carrier, with a
+/// `flip_any` method that works like `flip` for any carrier.
+///
+/// `Option<Result<T, E>>` becomes `Result<Option<T>, E>`, `Vec<Option<T>>`
+/// becomes `Option<Vec<T>>`, and so on.
+///
+/// # Type parameters
+///
+/// - `R`: The inner `Try` carrier
+pub trait FlipAnyExt<R: Try> {
+    /// The outer container, holding the outputs of the carriers
+    type Container;
+
+    fn flip_any(self) -> <R::Residual as Residual<Self::Container>>::TryType where
+        R::Residual: Residual<Self::Container>;
+}
+
+impl<R: Try> FlipAnyExt<R> for Option<R> {
+    type Container = Option<R::Output>;
+
+    fn flip_any(self) -> <R::Residual as Residual<Option<R::Output>>>::TryType where
+        R::Residual: Residual<Option<R::Output>>
+    {
+        match self {
+            Some(r) => match r.branch() {
+                ControlFlow::Continue(t) => Try::from_output(Some(t)),
+                ControlFlow::Break(residual) => FromResidual::from_residual(residual),
+            },
+            None => Try::from_output(None),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: Try> FlipAnyExt<R> for Vec<R> {
+    type Container = Vec<R::Output>;
+
+    fn flip_any(self) -> <R::Residual as Residual<Vec<R::Output>>>::TryType where
+        R::Residual: Residual<Vec<R::Output>>
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        for r in self {
+            match r.branch() {
+                ControlFlow::Continue(t) => result_vec.push(t),
+                ControlFlow::Break(residual) => return FromResidual::from_residual(residual),
+            }
+        }
+        Try::from_output(result_vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FlipAnyExt, TryMapAnyExt};
+
+    #[test]
+    fn test_try_map_any_result() {
+        assert_eq!(Some("42").try_map_any(|s| s.parse::<i32>()), Ok(Some(42)));
+        assert!(Some("x").try_map_any(|s| s.parse::<i32>()).is_err());
+    }
+
+    #[test]
+    fn test_try_map_any_option() {
+        assert_eq!(Some(4).try_map_any(|x: i32| x.checked_sub(1)), Some(Some(3)));
+        assert_eq!(Some(0u32).try_map_any(|x| x.checked_sub(1)), None);
+        assert_eq!(None.try_map_any(|x: u32| x.checked_sub(1)), Some(None));
+    }
+
+    #[test]
+    fn test_flip_any_vec() {
+        assert_eq!(vec![Some(1), Some(2)].flip_any(), Some(vec![1, 2]));
+        assert_eq!(vec![Ok(1), Err("oh noes")].flip_any(), Err("oh noes"));
+    }
+}
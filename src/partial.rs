@@ -0,0 +1,222 @@
+use alloc::vec::{self, Vec};
+use core::mem;
+
+/// The outcome of a conversion that failed part way through
+///
+/// Instead of throwing away all the completed work, this keeps the successfully
+/// converted prefix along with the error and the input that wasn't consumed yet,
+/// so the caller can deal with the problem and resume.
+///
+/// # Type parameters
+///
+/// - `C`: The type of the converted prefix
+/// - `I`: The type of the remaining input
+/// - `E`: The error that stopped the conversion
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PartialResult<C, I, E> {
+    /// The successfully converted prefix
+    pub done: C,
+    /// The error that stopped the conversion
+    pub error: E,
+    /// The input following the element that failed
+    pub rest: I,
+}
+
+/// Extend `Vec` with a `try_map_partial` method that applies a fallible function
+/// to every element, and on failure returns a `PartialResult` instead of just the error.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Vec`'s value type
+/// - `U`: The output `Vec`'s value type
+/// - `E`: The possible error during the mapping
+pub trait FallibleMapPartialExt<T, U, E> {
+
+    /// Apply a fallible map function to every element, keeping the completed work on failure
+    fn try_map_partial<F>(self, f: F) -> Result<Vec<U>, PartialResult<Vec<U>, Vec<T>, E>> where
+        F: FnMut(T) -> Result<U, E>;
+
+}
+
+impl<T, U, E> FallibleMapPartialExt<T, U, E> for Vec<T> {
+    fn try_map_partial<F>(self, mut f: F) -> Result<Vec<U>, PartialResult<Vec<U>, Vec<T>, E>> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        let mut iter = self.into_iter();
+        while let Some(t) = iter.next() {
+            match f(t) {
+                Ok(u) => result_vec.push(u),
+                Err(error) => return Err(PartialResult {
+                    done: result_vec,
+                    error,
+                    rest: iter.collect(),
+                }),
+            }
+        }
+        Ok(result_vec)
+    }
+}
+
+/// The `PartialResult` of `flip_resumable`: the flipped prefix, the first error
+/// and the unflipped rest
+pub type PartialFlip<T, E> = PartialResult<Vec<T>, Vec<Result<T, E>>, E>;
+
+/// Extend `Vec<Result<T>>` with a `flip_resumable` method that works like `flip`,
+/// but on failure returns a `PartialResult` instead of just the error.
+///
+/// The `rest` of the `PartialResult` contains the elements following the first
+/// error, still unflipped, so the caller can handle the error and flip them too.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait FlipResumableExt<T, E> {
+
+    /// Flip, keeping the values before the first error and the elements after it on failure
+    fn flip_resumable(self) -> Result<Vec<T>, PartialFlip<T, E>>;
+
+}
+
+impl<T, E> FlipResumableExt<T, E> for Vec<Result<T, E>> {
+    fn flip_resumable(mut self) -> Result<Vec<T>, PartialFlip<T, E>>
+    {
+        let failed = self.iter().position(Result::is_err);
+        let (error, rest) = match failed {
+            Some(i) => {
+                let rest = self.split_off(i + 1);
+                match self.pop() {
+                    Some(Err(e)) => (Some(e), rest),
+                    _ => unreachable!(),
+                }
+            },
+            None => (None, Vec::new()),
+        };
+        let done = self.into_iter().map(|r| match r {
+            Ok(t) => t,
+            Err(_) => unreachable!(),
+        }).collect();
+        match error {
+            Some(error) => Err(PartialResult { done, error, rest }),
+            None => Ok(done),
+        }
+    }
+}
+
+/// Extend `Vec` with a `try_drain_map` method that removes and converts the
+/// elements one at a time, stopping at the first error.
+///
+/// On error, the `Vec` is left with the elements that follow the failing one,
+/// so the caller can deal with the problem and resume draining. The failing
+/// element itself is consumed by the closure; include it in the error if it's
+/// needed for recovery.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Vec`'s value type
+/// - `U`: The output `Vec`'s value type
+/// - `E`: The possible error during the mapping
+pub trait FallibleDrainMapExt<T, U, E> {
+
+    /// Remove and convert the elements, returning the converted ones along with the error on failure
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` panics. The `Vec` is then left with the elements that
+    /// follow the one being converted, as on error, and the converted elements
+    /// are dropped.
+    fn try_drain_map<F>(&mut self, f: F) -> Result<Vec<U>, (Vec<U>, E)> where
+        F: FnMut(T) -> Result<U, E>;
+
+}
+
+/// Puts the elements that weren't drained yet back into the `Vec` when dropped,
+/// so that they survive both an error and a panic in the closure
+struct RestoreRest<'a, T> {
+    vec: &'a mut Vec<T>,
+    rest: vec::IntoIter<T>,
+}
+
+impl<'a, T> Drop for RestoreRest<'a, T> {
+    fn drop(&mut self) {
+        // Collecting the rest reuses the original allocation
+        *self.vec = mem::take(&mut self.rest).collect();
+    }
+}
+
+impl<T, U, E> FallibleDrainMapExt<T, U, E> for Vec<T> {
+    fn try_drain_map<F>(&mut self, mut f: F) -> Result<Vec<U>, (Vec<U>, E)> where
+        F: FnMut(T) -> Result<U, E>
+    {
+        let mut result_vec = Vec::with_capacity(self.len());
+        let rest = mem::take(self).into_iter();
+        let mut guard = RestoreRest { vec: self, rest };
+        for t in guard.rest.by_ref() {
+            match f(t) {
+                Ok(u) => result_vec.push(u),
+                Err(e) => return Err((result_vec, e)),
+            }
+        }
+        Ok(result_vec)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use {FallibleDrainMapExt, FallibleMapPartialExt, FlipResumableExt, PartialResult};
+
+    #[test]
+    fn test_try_map_partial_1() {
+        let x = vec!["1", "2", "3"].try_map_partial(|s| s.parse::<i32>().map_err(|_| s));
+        assert_eq!(x, Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_try_map_partial_2() {
+        let x = vec!["1", "x", "3", "4"].try_map_partial(|s| s.parse::<i32>().map_err(|_| s));
+        assert_eq!(x, Err(PartialResult { done: vec![1], error: "x", rest: vec!["3", "4"] }));
+    }
+
+    #[test]
+    fn test_try_map_partial_resume() {
+        let parse = |s: &'static str| s.parse::<i32>().map_err(|_| s);
+        let p = vec!["1", "x", "3"].try_map_partial(parse).unwrap_err();
+        let mut done = p.done;
+        done.push(0);
+        done.extend(p.rest.try_map_partial(parse).unwrap());
+        assert_eq!(done, vec![1, 0, 3]);
+    }
+
+    #[test]
+    fn test_try_drain_map() {
+        let mut v = vec!["1", "2", "x", "4", "5"];
+        let parse = |s: &'static str| s.parse::<i32>().map_err(|_| s);
+        assert_eq!(v.try_drain_map(parse), Err((vec![1, 2], "x")));
+        assert_eq!(v, vec!["4", "5"]);
+        assert_eq!(v.try_drain_map(parse), Ok(vec![4, 5]));
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn test_try_drain_map_panic() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut v = vec!["1", "2", "x", "4", "5"];
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            v.try_drain_map(|s| s.parse::<i32>().map_err(|_| ()).map(|n| n / (n - 2)))
+        }));
+        assert!(result.is_err());
+        assert_eq!(v, vec!["x", "4", "5"]);
+    }
+
+    #[test]
+    fn test_flip_resumable() {
+        let x = vec![Ok(1), Ok(2), Err("oh noes"), Ok(4), Err("oh foes")];
+        let p = x.flip_resumable().unwrap_err();
+        assert_eq!(p, PartialResult { done: vec![1, 2], error: "oh noes", rest: vec![Ok(4), Err("oh foes")] });
+        let p = p.rest.flip_resumable().unwrap_err();
+        assert_eq!(p, PartialResult { done: vec![4], error: "oh foes", rest: vec![] });
+        assert_eq!(vec![Ok::<_, ()>(1), Ok(2)].flip_resumable(), Ok(vec![1, 2]));
+    }
+}
@@ -0,0 +1,223 @@
+//! Error policies for `flip_with_policy`
+//!
+//! A policy selects what happens when flipping a collection encounters errors.
+//! The policies are types rather than values, so that each one can have the
+//! return type that fits it best.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+use {FlipAllExt, Flippable, PartitionFlipExt};
+
+/// Marker trait for the error policies accepted by `flip_with_policy`
+pub trait ErrorPolicy {}
+
+/// Stop at the first error and return it, like `flip`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FailFast;
+
+/// Go through every element and return either all the values or all the errors, like `flip_all`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AccumulateAll;
+
+/// Go through every element and return both the values and the errors, like `partition_flip`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KeepPartial;
+
+impl ErrorPolicy for FailFast {}
+impl ErrorPolicy for AccumulateAll {}
+impl ErrorPolicy for KeepPartial {}
+
+/// Extend `Vec<Result<T>>`, `Option<Result<T>>`, `HashMap<K, Result<V>>` and
+/// `BTreeMap<K, Result<V>>` with a `flip_with_policy` method that flips
+/// according to an `ErrorPolicy`.
+///
+/// This gives the fail-fast, accumulate-all and keep-partial behaviours a single
+/// consistent API across containers:
+///
+/// - `FailFast` returns `Result<C<T>, E>`
+/// - `AccumulateAll` returns `Result<C<T>, Vec<E>>`
+/// - `KeepPartial` returns `(C<T>, Vec<E>)`
+///
+/// For the maps, the errors are collected in iteration order, and `KeepPartial`
+/// keeps the entries whose values succeeded.
+///
+/// # Type parameters
+///
+/// - `P`: The error policy
+pub trait FlipWithPolicyExt<P: ErrorPolicy> {
+    type Output;
+
+    fn flip_with_policy(self, policy: P) -> Self::Output;
+}
+
+impl<T, E> FlipWithPolicyExt<FailFast> for Vec<Result<T, E>> {
+    type Output = Result<Vec<T>, E>;
+
+    fn flip_with_policy(self, _: FailFast) -> Result<Vec<T>, E> {
+        self.flip()
+    }
+}
+
+impl<T, E> FlipWithPolicyExt<AccumulateAll> for Vec<Result<T, E>> {
+    type Output = Result<Vec<T>, Vec<E>>;
+
+    fn flip_with_policy(self, _: AccumulateAll) -> Result<Vec<T>, Vec<E>> {
+        self.flip_all()
+    }
+}
+
+impl<T, E> FlipWithPolicyExt<KeepPartial> for Vec<Result<T, E>> {
+    type Output = (Vec<T>, Vec<E>);
+
+    fn flip_with_policy(self, _: KeepPartial) -> (Vec<T>, Vec<E>) {
+        self.partition_flip()
+    }
+}
+
+impl<T, E> FlipWithPolicyExt<FailFast> for Option<Result<T, E>> {
+    type Output = Result<Option<T>, E>;
+
+    fn flip_with_policy(self, _: FailFast) -> Result<Option<T>, E> {
+        self.flip()
+    }
+}
+
+impl<T, E> FlipWithPolicyExt<AccumulateAll> for Option<Result<T, E>> {
+    type Output = Result<Option<T>, Vec<E>>;
+
+    fn flip_with_policy(self, _: AccumulateAll) -> Result<Option<T>, Vec<E>> {
+        self.flip().map_err(|e| vec![e])
+    }
+}
+
+impl<T, E> FlipWithPolicyExt<KeepPartial> for Option<Result<T, E>> {
+    type Output = (Option<T>, Vec<E>);
+
+    fn flip_with_policy(self, _: KeepPartial) -> (Option<T>, Vec<E>) {
+        match self {
+            Some(Ok(t)) => (Some(t), Vec::new()),
+            Some(Err(e)) => (None, vec![e]),
+            None => (None, Vec::new()),
+        }
+    }
+}
+
+/// Split map entries into the successful entries and the errors
+fn partition_entries<K, V, E, M, I>(entries: I) -> (M, Vec<E>) where
+    M: Default + Extend<(K, V)>,
+    I: IntoIterator<Item = (K, Result<V, E>)>
+{
+    let mut result_map = M::default();
+    let mut errors = Vec::new();
+    for (k, v) in entries {
+        match v {
+            Ok(v) => result_map.extend(Some((k, v))),
+            Err(e) => errors.push(e),
+        }
+    }
+    (result_map, errors)
+}
+
+impl<K: Ord, V, E> FlipWithPolicyExt<FailFast> for BTreeMap<K, Result<V, E>> {
+    type Output = Result<BTreeMap<K, V>, E>;
+
+    fn flip_with_policy(self, _: FailFast) -> Result<BTreeMap<K, V>, E> {
+        self.flip()
+    }
+}
+
+impl<K: Ord, V, E> FlipWithPolicyExt<AccumulateAll> for BTreeMap<K, Result<V, E>> {
+    type Output = Result<BTreeMap<K, V>, Vec<E>>;
+
+    fn flip_with_policy(self, _: AccumulateAll) -> Result<BTreeMap<K, V>, Vec<E>> {
+        match partition_entries(self) {
+            (result_map, ref errors) if errors.is_empty() => Ok(result_map),
+            (_, errors) => Err(errors),
+        }
+    }
+}
+
+impl<K: Ord, V, E> FlipWithPolicyExt<KeepPartial> for BTreeMap<K, Result<V, E>> {
+    type Output = (BTreeMap<K, V>, Vec<E>);
+
+    fn flip_with_policy(self, _: KeepPartial) -> (BTreeMap<K, V>, Vec<E>) {
+        partition_entries(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, E, S: BuildHasher + Default> FlipWithPolicyExt<FailFast> for HashMap<K, Result<V, E>, S> {
+    type Output = Result<HashMap<K, V, S>, E>;
+
+    fn flip_with_policy(self, _: FailFast) -> Result<HashMap<K, V, S>, E> {
+        self.flip()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, E, S: BuildHasher + Default> FlipWithPolicyExt<AccumulateAll> for HashMap<K, Result<V, E>, S> {
+    type Output = Result<HashMap<K, V, S>, Vec<E>>;
+
+    fn flip_with_policy(self, _: AccumulateAll) -> Result<HashMap<K, V, S>, Vec<E>> {
+        match partition_entries(self) {
+            (result_map, ref errors) if errors.is_empty() => Ok(result_map),
+            (_, errors) => Err(errors),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, E, S: BuildHasher + Default> FlipWithPolicyExt<KeepPartial> for HashMap<K, Result<V, E>, S> {
+    type Output = (HashMap<K, V, S>, Vec<E>);
+
+    fn flip_with_policy(self, _: KeepPartial) -> (HashMap<K, V, S>, Vec<E>) {
+        partition_entries(self)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use FlipWithPolicyExt;
+    use super::{AccumulateAll, FailFast, KeepPartial};
+
+    fn input() -> Vec<Result<i32, &'static str>> {
+        vec![Ok(1), Err("oh noes"), Ok(3), Err("oh foes")]
+    }
+
+    #[test]
+    fn test_flip_with_policy_vec() {
+        assert_eq!(input().flip_with_policy(FailFast), Err("oh noes"));
+        assert_eq!(input().flip_with_policy(AccumulateAll), Err(vec!["oh noes", "oh foes"]));
+        assert_eq!(input().flip_with_policy(KeepPartial), (vec![1, 3], vec!["oh noes", "oh foes"]));
+    }
+
+    #[test]
+    fn test_flip_with_policy_option() {
+        let x: Option<Result<i32, &'static str>> = Some(Err("oh noes"));
+        assert_eq!(x.flip_with_policy(FailFast), Err("oh noes"));
+        assert_eq!(x.flip_with_policy(AccumulateAll), Err(vec!["oh noes"]));
+        assert_eq!(x.flip_with_policy(KeepPartial), (None, vec!["oh noes"]));
+        assert_eq!(Some(Ok::<_, ()>(42)).flip_with_policy(KeepPartial), (Some(42), vec![]));
+    }
+
+    #[test]
+    fn test_flip_with_policy_maps() {
+        use std::collections::{BTreeMap, HashMap};
+
+        let x = || input().into_iter().enumerate().collect::<BTreeMap<_, _>>();
+        assert_eq!(x().flip_with_policy(FailFast), Err("oh noes"));
+        assert_eq!(x().flip_with_policy(AccumulateAll), Err(vec!["oh noes", "oh foes"]));
+        assert_eq!(x().flip_with_policy(KeepPartial), (vec![(0, 1), (2, 3)].into_iter().collect(), vec!["oh noes", "oh foes"]));
+        let x = || vec![("a", Ok(1)), ("b", Err("oh noes"))].into_iter().collect::<HashMap<_, _>>();
+        assert_eq!(x().flip_with_policy(FailFast), Err("oh noes"));
+        assert_eq!(x().flip_with_policy(AccumulateAll), Err(vec!["oh noes"]));
+        assert_eq!(x().flip_with_policy(KeepPartial), (vec![("a", 1)].into_iter().collect(), vec!["oh noes"]));
+        let x: HashMap<_, Result<i32, ()>> = vec![("a", Ok(1))].into_iter().collect();
+        assert_eq!(x.flip_with_policy(AccumulateAll), Ok(vec![("a", 1)].into_iter().collect()));
+    }
+}
@@ -0,0 +1,30 @@
+//! All the extension traits of the crate in a single module
+//!
+//! A glob import brings every method into scope:
+//!
+//! ```
+//! use try_map::prelude::*;
+//!
+//! assert_eq!(Some("42").try_map(|s| s.parse::<i32>()), Ok(Some(42)));
+//! assert_eq!(Some(Err::<i32, _>("oh noes")).flip(), Err("oh noes"));
+//! ```
+//!
+//! Only the traits enabled by the active features are exported.
+
+pub use {Combine, FallibleMapBreakExt, FallibleMapCellExt, FallibleMapExt, FallibleMapFullExt, FallibleMapMutExt, FallibleMapOptExt, FallibleMapRefCellExt, FallibleZipExt, FlipContextExt, FlipFlattenExt, FlipMapErrExt, FlipZipExt, Flippable, Sequence, Traverse, TryBinarySearchExt, TryDefault, TryDefaultExt, TryExtend, TryFunctor, TryOnceExt, TryParseExt, TryUpdateExt};
+#[cfg(feature = "alloc")]
+pub use {FallibleDrainMapExt, FallibleGroupMapExt, FallibleMapAllExt, FallibleMapCharsExt, FallibleMapChunksExt, FallibleMapEntriesExt, FallibleMapFoldExt, FallibleMapKeysExt, FallibleMapPartialExt, FallibleMapValuesMutExt, FallibleMapWindowsExt, FallibleZipMapByKeyExt, FallibleZipMapExt, FirstOkExt, FlipAllExt, FlipIndexedExt, FlipLossyExt, FlipOkExt, FlipOrDefaultExt, FlipResumableExt, FlipWithPolicyExt, IntoErrorsExt, PartitionFlipExt, TryChainRun, TryEntryExt, TryMergeExt, TryOrElseChainExt};
+#[cfg(feature = "std")]
+pub use FallibleMapLockedExt;
+#[cfg(feature = "threads")]
+pub use {FallibleMapMutParallelExt, FallibleMapParallelExt, FlipParallelExt};
+#[cfg(feature = "async")]
+pub use {FallibleFilterAsyncExt, FallibleMapAsyncExt, FallibleMapAsyncTimeoutExt, FutureFlipExt};
+#[cfg(all(feature = "async", feature = "alloc"))]
+pub use {FallibleMapConcurrentExt, FutureFlipAllExt, FutureFlipOrderedExt};
+#[cfg(feature = "async-fn")]
+pub use FallibleMapAsyncFnExt;
+#[cfg(all(feature = "async-fn", feature = "alloc"))]
+pub use FallibleMapConcurrentAsyncFnExt;
+#[cfg(feature = "nightly")]
+pub use {FlipAnyExt, TryMapAnyExt};
@@ -0,0 +1,220 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Extend slices, and thereby `Vec`s, with a binary search that uses a fallible comparator.
+///
+/// This is `binary_search_by` for keys that have to be decoded, or otherwise
+/// computed in a way that can fail, before they can be compared. The search
+/// stops at the first error.
+///
+/// # Type parameters
+///
+/// - `T`: The element type
+pub trait TryBinarySearchExt<T> {
+
+    /// Binary search the sorted slice with a fallible comparator
+    ///
+    /// Like `binary_search_by`, the comparator returns the ordering of the
+    /// element relative to the target, and the result is `Ok(index)` of a
+    /// matching element or `Err(index)` where it could be inserted.
+    fn try_binary_search_by<E, F>(&self, f: F) -> Result<Result<usize, usize>, E> where
+        F: FnMut(&T) -> Result<Ordering, E>;
+
+}
+
+impl<T> TryBinarySearchExt<T> for [T] {
+    fn try_binary_search_by<E, F>(&self, mut f: F) -> Result<Result<usize, usize>, E> where
+        F: FnMut(&T) -> Result<Ordering, E>
+    {
+        let mut low = 0;
+        let mut high = self.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match f(&self[mid])? {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(Ok(mid)),
+            }
+        }
+        Ok(Err(low))
+    }
+}
+
+/// Extend slices, and thereby `Vec`s, with methods that mutate the elements in
+/// place with a fallible function.
+///
+/// No allocation is made. What happens to the elements preceding an error is up
+/// to the caller: `try_map_mut` leaves them mutated, while `try_map_mut_or_undo`
+/// rolls them back with an undo function.
+///
+/// # Type parameters
+///
+/// - `T`: The element type
+pub trait FallibleMapMutExt<T> {
+
+    /// Mutate every element, stopping at the first error and keeping the earlier mutations
+    fn try_map_mut<E, F>(&mut self, f: F) -> Result<(), E> where
+        F: FnMut(&mut T) -> Result<(), E>;
+
+    /// Mutate every element, stopping at the first error and undoing the earlier mutations
+    ///
+    /// On error, `undo` is called on the elements that were successfully mutated,
+    /// in reverse order. The element for which `f` failed isn't passed to `undo`.
+    fn try_map_mut_or_undo<E, F, G>(&mut self, f: F, undo: G) -> Result<(), E> where
+        F: FnMut(&mut T) -> Result<(), E>,
+        G: FnMut(&mut T);
+
+}
+
+impl<T> FallibleMapMutExt<T> for [T] {
+    fn try_map_mut<E, F>(&mut self, f: F) -> Result<(), E> where
+        F: FnMut(&mut T) -> Result<(), E>
+    {
+        self.iter_mut().try_for_each(f)
+    }
+
+    fn try_map_mut_or_undo<E, F, G>(&mut self, mut f: F, mut undo: G) -> Result<(), E> where
+        F: FnMut(&mut T) -> Result<(), E>,
+        G: FnMut(&mut T)
+    {
+        for i in 0..self.len() {
+            if let Err(e) = f(&mut self[i]) {
+                self[..i].iter_mut().rev().for_each(&mut undo);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extend slices, and thereby `Vec`s, with a `try_map_chunks` method that applies
+/// a fallible function to consecutive chunks of the elements.
+///
+/// The last chunk is shorter if the length isn't divisible by the chunk size.
+/// The mapping stops at the first error.
+///
+/// # Type parameters
+///
+/// - `T`: The element type
+#[cfg(feature = "alloc")]
+pub trait FallibleMapChunksExt<T> {
+
+    /// Apply a fallible map function to every chunk of `chunk_size` elements
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    fn try_map_chunks<U, E, F>(&self, chunk_size: usize, f: F) -> Result<Vec<U>, E> where
+        F: FnMut(&[T]) -> Result<U, E>;
+
+}
+
+#[cfg(feature = "alloc")]
+impl<T> FallibleMapChunksExt<T> for [T] {
+    fn try_map_chunks<U, E, F>(&self, chunk_size: usize, f: F) -> Result<Vec<U>, E> where
+        F: FnMut(&[T]) -> Result<U, E>
+    {
+        self.chunks(chunk_size).map(f).collect()
+    }
+}
+
+/// Extend slices, and thereby `Vec`s, with a `try_windows_map` method that applies
+/// a fallible function to every overlapping window of the elements.
+///
+/// If the slice is shorter than the window size, there are no windows and the
+/// result is an empty `Vec`. The mapping stops at the first error.
+///
+/// # Type parameters
+///
+/// - `T`: The element type
+#[cfg(feature = "alloc")]
+pub trait FallibleMapWindowsExt<T> {
+
+    /// Apply a fallible map function to every window of `size` elements
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    fn try_windows_map<U, E, F>(&self, size: usize, f: F) -> Result<Vec<U>, E> where
+        F: FnMut(&[T]) -> Result<U, E>;
+
+}
+
+#[cfg(feature = "alloc")]
+impl<T> FallibleMapWindowsExt<T> for [T] {
+    fn try_windows_map<U, E, F>(&self, size: usize, f: F) -> Result<Vec<U>, E> where
+        F: FnMut(&[T]) -> Result<U, E>
+    {
+        self.windows(size).map(f).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use test_util::increment;
+    #[cfg(feature = "std")]
+    use {FallibleMapChunksExt, FallibleMapMutExt, FallibleMapWindowsExt};
+    use TryBinarySearchExt;
+
+    #[test]
+    fn test_try_binary_search_by() {
+        let keys = ["1", "3", "5", "7"];
+        let search = |target: i32| keys.try_binary_search_by(|k| k.parse::<i32>().map(|k| k.cmp(&target)));
+        assert_eq!(search(5), Ok(Ok(2)));
+        assert_eq!(search(4), Ok(Err(2)));
+        assert_eq!(search(8), Ok(Err(4)));
+        let empty: &[i32] = &[];
+        assert_eq!(empty.try_binary_search_by(|_| Err(())), Ok(Err(0)));
+    }
+
+    #[test]
+    fn test_try_binary_search_by_corrupt() {
+        let keys = ["1", "3", "x", "7"];
+        assert!(keys.try_binary_search_by(|k| k.parse::<i32>().map(|k| k.cmp(&7))).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_try_map_chunks() {
+        let sum = |c: &[i32]| if c.len() == 2 { Ok(c[0] + c[1]) } else { Err(c.len()) };
+        assert_eq!([1, 2, 3, 4].try_map_chunks(2, sum), Ok(vec![3, 7]));
+        assert_eq!([1, 2, 3, 4, 5].try_map_chunks(2, sum), Err(1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    #[should_panic]
+    fn test_try_map_chunks_zero() {
+        let _ = [1, 2].try_map_chunks(0, |c| Ok::<_, ()>(c.len()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_try_windows_map() {
+        let step = |w: &[u32]| w[1].checked_sub(w[0]).ok_or(w[0]);
+        assert_eq!([1, 2, 4, 7].try_windows_map(2, step), Ok(vec![1, 2, 3]));
+        assert_eq!([1, 4, 2, 7].try_windows_map(2, step), Err(4));
+        assert_eq!([1].try_windows_map(2, step), Ok(vec![]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_try_map_mut() {
+        let mut v = vec![1u8, 2, 255, 4];
+        assert_eq!(v.try_map_mut(increment), Err(255));
+        assert_eq!(v, vec![2, 3, 255, 4]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_try_map_mut_or_undo() {
+        let mut v = vec![1u8, 2, 255, 4];
+        assert_eq!(v.try_map_mut_or_undo(increment, |x| *x -= 1), Err(255));
+        assert_eq!(v, vec![1, 2, 255, 4]);
+        let mut v = vec![1u8, 2];
+        assert_eq!(v.try_map_mut_or_undo(increment, |x| *x -= 1), Ok(()));
+        assert_eq!(v, vec![2, 3]);
+    }
+}
@@ -0,0 +1,74 @@
+use alloc::string::String;
+
+/// Extend `str`, and thereby `String`, with `try_map_chars` methods that build a
+/// new string from a fallible per-character conversion.
+///
+/// The output is preallocated with the length of the input, so conversions that
+/// don't grow the text allocate once. The conversion stops at the first error.
+pub trait FallibleMapCharsExt {
+
+    /// Convert every character with a fallible function
+    fn try_map_chars<E, F>(&self, f: F) -> Result<String, E> where
+        F: FnMut(char) -> Result<char, E>;
+
+    /// Convert every character into a string slice with a fallible function,
+    /// e.g. for escaping
+    fn try_map_chars_str<S, E, F>(&self, f: F) -> Result<String, E> where
+        S: AsRef<str>,
+        F: FnMut(char) -> Result<S, E>;
+
+}
+
+impl FallibleMapCharsExt for str {
+    fn try_map_chars<E, F>(&self, mut f: F) -> Result<String, E> where
+        F: FnMut(char) -> Result<char, E>
+    {
+        let mut result = String::with_capacity(self.len());
+        for c in self.chars() {
+            result.push(f(c)?);
+        }
+        Ok(result)
+    }
+
+    fn try_map_chars_str<S, E, F>(&self, mut f: F) -> Result<String, E> where
+        S: AsRef<str>,
+        F: FnMut(char) -> Result<S, E>
+    {
+        let mut result = String::with_capacity(self.len());
+        for c in self.chars() {
+            result.push_str(f(c)?.as_ref());
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use FallibleMapCharsExt;
+
+    fn rot13(c: char) -> Result<char, char> {
+        match c {
+            'a'..='z' => Ok((((c as u8 - b'a') + 13) % 26 + b'a') as char),
+            ' ' => Ok(' '),
+            _ => Err(c),
+        }
+    }
+
+    #[test]
+    fn test_try_map_chars() {
+        assert_eq!("oh noes".try_map_chars(rot13), Ok("bu abrf".to_string()));
+        assert_eq!("oh Noes".to_string().try_map_chars(rot13), Err('N'));
+    }
+
+    #[test]
+    fn test_try_map_chars_str() {
+        let escape = |c: char| match c {
+            '<' => Ok("&lt;".to_string()),
+            '>' => Ok("&gt;".to_string()),
+            '\0' => Err("NUL in input"),
+            c => Ok(c.to_string()),
+        };
+        assert_eq!("<b>".try_map_chars_str(escape), Ok("&lt;b&gt;".to_string()));
+        assert_eq!("a\0b".try_map_chars_str(escape), Err("NUL in input"));
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::{Mutex, OnceLock, PoisonError, RwLock};
+
+use TryOnceExt;
+
+/// Extend `Mutex<Option<T>>` and `RwLock<Option<T>>` with a `try_map_locked`
+/// method that applies a fallible function to the contents in place.
+///
+/// The lock is held, for writing in the case of `RwLock`, for the duration of
+/// the call. If the lock is poisoned, a `PoisonError<()>` is converted into the
+/// error type, and the function isn't called.
+///
+/// # Type parameters
+///
+/// - `T`: The type of the contents
+pub trait FallibleMapLockedExt<T> {
+
+    /// Lock and apply a fallible function to the contents, if any
+    fn try_map_locked<U, E, F>(&self, f: F) -> Result<Option<U>, E> where
+        E: From<PoisonError<()>>,
+        F: FnOnce(&mut T) -> Result<U, E>;
+
+}
+
+impl<T> FallibleMapLockedExt<T> for Mutex<Option<T>> {
+    fn try_map_locked<U, E, F>(&self, f: F) -> Result<Option<U>, E> where
+        E: From<PoisonError<()>>,
+        F: FnOnce(&mut T) -> Result<U, E>
+    {
+        let mut guard = self.lock().map_err(|_| PoisonError::new(()))?;
+        match guard.as_mut() {
+            Some(t) => f(t).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T> FallibleMapLockedExt<T> for RwLock<Option<T>> {
+    fn try_map_locked<U, E, F>(&self, f: F) -> Result<Option<U>, E> where
+        E: From<PoisonError<()>>,
+        F: FnOnce(&mut T) -> Result<U, E>
+    {
+        let mut guard = self.write().map_err(|_| PoisonError::new(()))?;
+        match guard.as_mut() {
+            Some(t) => f(t).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T> TryOnceExt<T> for OnceLock<T> {
+    fn get_or_try_init_with<E, F>(&self, f: F) -> Result<&T, E> where
+        F: FnOnce() -> Result<T, E>
+    {
+        if let Some(t) = self.get() {
+            return Ok(t);
+        }
+        let t = f()?;
+        Ok(self.get_or_init(|| t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex, OnceLock, RwLock};
+    use std::thread;
+
+    use test_util::{try_increment, StateError};
+    use {FallibleMapLockedExt, TryOnceExt};
+
+    #[test]
+    fn test_try_map_locked() {
+        let cache = Mutex::new(Some(1u8));
+        assert_eq!(cache.try_map_locked(try_increment), Ok(Some(2)));
+        let cache = RwLock::new(Some(255u8));
+        assert_eq!(cache.try_map_locked(try_increment), Err(StateError::Overflow));
+        let cache = RwLock::new(None);
+        assert_eq!(cache.try_map_locked(try_increment), Ok(None));
+    }
+
+    #[test]
+    fn test_try_map_locked_poisoned() {
+        let cache = Arc::new(Mutex::new(Some(1u8)));
+        let c = cache.clone();
+        let _ = thread::spawn(move || {
+            let _guard = c.lock().unwrap();
+            panic!("poisoning the lock");
+        }).join();
+        assert_eq!(cache.try_map_locked(try_increment), Err(StateError::Poisoned));
+    }
+
+    #[test]
+    fn test_get_or_try_init_once_lock() {
+        static CONFIG: OnceLock<u16> = OnceLock::new();
+        assert!(CONFIG.get_or_try_init_with(|| "70000".parse::<u16>()).is_err());
+        let threads: Vec<_> = (0..4).map(|_| thread::spawn(|| CONFIG.get_or_try_init_with(|| "8080".parse::<u16>()).copied())).collect();
+        for t in threads {
+            assert_eq!(t.join().unwrap(), Ok(8080));
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Fixtures shared by the tests of the in-place mutation methods
+
+use std::cell::BorrowMutError;
+use std::sync::PoisonError;
+
+/// The errors of the cell and lock tests, with conversions from their access errors
+#[derive(Debug, PartialEq)]
+pub enum StateError {
+    Busy,
+    Poisoned,
+    Overflow,
+}
+
+impl From<BorrowMutError> for StateError {
+    fn from(_: BorrowMutError) -> StateError {
+        StateError::Busy
+    }
+}
+
+impl From<PoisonError<()>> for StateError {
+    fn from(_: PoisonError<()>) -> StateError {
+        StateError::Poisoned
+    }
+}
+
+/// Increment the value in place, failing with the unchanged value on overflow
+pub fn increment(x: &mut u8) -> Result<(), u8> {
+    x.checked_add(1).map(|y| *x = y).ok_or(*x)
+}
+
+/// Increment the value in place and return the new value
+pub fn try_increment(x: &mut u8) -> Result<u8, StateError> {
+    *x = x.checked_add(1).ok_or(StateError::Overflow)?;
+    Ok(*x)
+}
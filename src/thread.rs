@@ -0,0 +1,325 @@
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use FallibleMapAllExt;
+
+/// Extend `Vec` with a fallible map method that runs on several threads
+///
+/// The input is split into one contiguous chunk per thread, and the chunks are
+/// mapped on scoped threads, so `f` can borrow from the caller. The output
+/// preserves the order of the input.
+///
+/// As soon as an element fails, the threads stop mapping the elements after it.
+/// The error is always the one of the failing element with the lowest index,
+/// whichever thread finds it first, so a failure is reproducible.
+/// `try_map_all_parallel` maps every element instead, and returns all the
+/// errors, in the order of the input.
+///
+/// This is a lightweight alternative to rayon: it spawns fresh threads on every
+/// call, so it's best suited for coarse-grained work.
+///
+/// # Type parameters
+///
+/// - `T`: The input `Vec`'s value type
+/// - `U`: The output `Vec`'s value type
+/// - `E`: The possible error during the mapping
+pub trait FallibleMapParallelExt<T, U, E> {
+
+    /// Try to apply a fallible map function to every element, using at most `threads` threads
+    ///
+    /// `std::thread::available_parallelism` is a good default for the thread count.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero, or if `f` panics.
+    fn try_map_parallel<F>(self, threads: usize, f: F) -> Result<Vec<U>, E> where
+        F: Fn(T) -> Result<U, E> + Sync;
+
+    /// Apply a fallible map function to every element, using at most `threads` threads, returning all values or all errors
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero, or if `f` panics.
+    fn try_map_all_parallel<F>(self, threads: usize, f: F) -> Result<Vec<U>, Vec<E>> where
+        F: Fn(T) -> Result<U, E> + Sync;
+
+}
+
+/// Split `input` into at most `threads` contiguous chunks, and run `g` on each
+/// of them on a scoped thread of its own
+///
+/// `g` also receives the index of the first element of its chunk. The outputs
+/// are in the order of the chunks. `input` must not be empty.
+fn map_chunks<T: Send, R: Send, G>(input: Vec<T>, threads: usize, g: G) -> Vec<R> where
+    G: Fn(usize, Vec<T>) -> R + Sync
+{
+    let chunk_len = (input.len() + threads - 1) / threads;
+    let mut input = input.into_iter();
+    let chunks: Vec<Vec<T>> = (0..(input.len() + chunk_len - 1) / chunk_len)
+        .map(|_| input.by_ref().take(chunk_len).collect())
+        .collect();
+    let g = &g;
+
+    thread::scope(|s| {
+        let handles: Vec<_> = chunks.into_iter().enumerate()
+            .map(|(c, chunk)| s.spawn(move || g(c * chunk_len, chunk)))
+            .collect();
+        handles.into_iter()
+            .map(|handle| handle.join().unwrap_or_else(|p| panic::resume_unwind(p)))
+            .collect()
+    })
+}
+
+impl<T: Send, U: Send, E: Send> FallibleMapParallelExt<T, U, E> for Vec<T> {
+    fn try_map_parallel<F>(self, threads: usize, f: F) -> Result<Vec<U>, E> where
+        F: Fn(T) -> Result<U, E> + Sync
+    {
+        assert!(threads > 0, "try_map_parallel: the thread count must be positive");
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        let failed_at = AtomicUsize::new(usize::MAX);
+        let failed_at = &failed_at;
+        let outputs = map_chunks(self, threads, |start, chunk| {
+            let mut result_vec = Vec::with_capacity(chunk.len());
+            for (i, t) in (start..).zip(chunk) {
+                if i > failed_at.load(Ordering::Relaxed) {
+                    break;
+                }
+                match f(t) {
+                    Ok(u) => result_vec.push(u),
+                    Err(e) => {
+                        failed_at.fetch_min(i, Ordering::Relaxed);
+                        return Err(e);
+                    },
+                }
+            }
+            Ok(result_vec)
+        });
+
+        // The chunks are contiguous, so the first failing chunk contains the lowest failing index.
+        let mut result_vec = Vec::with_capacity(outputs.len());
+        for output in outputs {
+            result_vec.extend(output?);
+        }
+        Ok(result_vec)
+    }
+
+    fn try_map_all_parallel<F>(self, threads: usize, f: F) -> Result<Vec<U>, Vec<E>> where
+        F: Fn(T) -> Result<U, E> + Sync
+    {
+        assert!(threads > 0, "try_map_all_parallel: the thread count must be positive");
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        let outputs = map_chunks(self, threads, |_, chunk| chunk.try_map_all(&f));
+
+        let mut result_vec = Vec::new();
+        let mut errors = Vec::new();
+        for output in outputs {
+            match output {
+                Ok(us) => if errors.is_empty() { result_vec.extend(us) },
+                Err(es) => errors.extend(es),
+            }
+        }
+        if errors.is_empty() { Ok(result_vec) } else { Err(errors) }
+    }
+}
+
+/// Extend slices, and thereby `Vec`s, with a `try_map_mut_parallel` method that
+/// mutates the elements in place on several threads.
+///
+/// This is the threaded counterpart of `try_map_mut`: the slice is split into one
+/// contiguous chunk per thread, and the chunks are mutated on scoped threads, so
+/// `f` can borrow from the caller. No element is copied or moved, which suits
+/// fix-ups of large buffers.
+///
+/// As soon as an element fails, the threads stop mutating the elements after it.
+/// The error is always the one of the failing element with the lowest index,
+/// whichever thread finds it first, so a failure is reproducible.
+///
+/// On error, every element before the failing one has been mutated. Whether the
+/// elements after it have been is unspecified, since the other threads may have
+/// got to them before noticing the failure.
+///
+/// This is a lightweight alternative to rayon: it spawns fresh threads on every
+/// call, so it's best suited for coarse-grained work.
+///
+/// # Type parameters
+///
+/// - `T`: The element type
+pub trait FallibleMapMutParallelExt<T> {
+
+    /// Mutate every element in place, using at most `threads` threads
+    ///
+    /// `std::thread::available_parallelism` is a good default for the thread count.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero, or if `f` panics.
+    fn try_map_mut_parallel<E, F>(&mut self, threads: usize, f: F) -> Result<(), E> where
+        E: Send,
+        F: Fn(&mut T) -> Result<(), E> + Sync;
+
+}
+
+impl<T: Send> FallibleMapMutParallelExt<T> for [T] {
+    fn try_map_mut_parallel<E, F>(&mut self, threads: usize, f: F) -> Result<(), E> where
+        E: Send,
+        F: Fn(&mut T) -> Result<(), E> + Sync
+    {
+        assert!(threads > 0, "try_map_mut_parallel: the thread count must be positive");
+        if self.is_empty() {
+            return Ok(());
+        }
+        let chunk_len = (self.len() + threads - 1) / threads;
+        let failed_at = AtomicUsize::new(usize::MAX);
+        let (f, failed_at) = (&f, &failed_at);
+
+        thread::scope(|s| {
+            let handles: Vec<_> = self.chunks_mut(chunk_len).enumerate()
+                .map(|(c, chunk)| s.spawn(move || {
+                    for (i, t) in (c * chunk_len..).zip(chunk) {
+                        if i > failed_at.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        if let Err(e) = f(t) {
+                            failed_at.fetch_min(i, Ordering::Relaxed);
+                            return Err(e);
+                        }
+                    }
+                    Ok(())
+                }))
+                .collect();
+
+            // The chunks are contiguous, so the first failing chunk contains the lowest failing index.
+            let mut result = Ok(());
+            for handle in handles {
+                let r = handle.join().unwrap_or_else(|p| panic::resume_unwind(p));
+                if result.is_ok() {
+                    result = r;
+                }
+            }
+            result
+        })
+    }
+}
+
+/// Extend `Vec<FnOnce() -> Result<T>>` with a `flip_parallel` method that runs
+/// the closures on several threads and flips the results into a `Result<Vec<T>>`.
+///
+/// This is the threaded analogue of `flip_future`, with the same guarantees as
+/// `try_map_parallel`: the order is preserved, the closures after a failing one
+/// are skipped, and the error is the one of the failing closure with the lowest
+/// index. `flip_all_parallel` runs every closure and returns all the errors.
+///
+/// # Type parameters
+///
+/// - `T`: The inner value type
+/// - `E`: The error type of `Result`
+pub trait FlipParallelExt<T, E> {
+
+    /// Run the closures using at most `threads` threads, stopping at the first error
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero, or if one of the closures panics.
+    fn flip_parallel(self, threads: usize) -> Result<Vec<T>, E>;
+
+    /// Run every closure using at most `threads` threads, returning all values or all errors
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero, or if one of the closures panics.
+    fn flip_all_parallel(self, threads: usize) -> Result<Vec<T>, Vec<E>>;
+
+}
+
+impl<F, T: Send, E: Send> FlipParallelExt<T, E> for Vec<F> where
+    F: FnOnce() -> Result<T, E> + Send
+{
+    fn flip_parallel(self, threads: usize) -> Result<Vec<T>, E>
+    {
+        self.try_map_parallel(threads, |f| f())
+    }
+
+    fn flip_all_parallel(self, threads: usize) -> Result<Vec<T>, Vec<E>>
+    {
+        self.try_map_all_parallel(threads, |f| f())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use {FallibleMapMutParallelExt, FallibleMapParallelExt, FlipParallelExt};
+
+    #[test]
+    fn test_try_map_parallel_1() {
+        let x: Vec<i32> = (0..100).collect();
+        assert_eq!(x.clone().try_map_parallel(4, |x| Ok::<_, ()>(x * 2)), Ok(x.iter().map(|x| x * 2).collect()));
+        assert_eq!(x.try_map_parallel(200, Ok::<_, ()>).map(|v| v.len()), Ok(100));
+        assert_eq!(Vec::<i32>::new().try_map_parallel(4, Ok::<_, ()>), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_try_map_parallel_lowest_index_error() {
+        let calls = AtomicUsize::new(0);
+        let x: Vec<i32> = (0..1000).collect();
+        let result = x.try_map_parallel(4, |x| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            if x % 300 == 299 { Err(x) } else { Ok(x) }
+        });
+        assert_eq!(result, Err(299));
+        // Every chunk stops at its first error, at the latest.
+        assert!(calls.load(Ordering::Relaxed) < 1000);
+    }
+
+    #[test]
+    fn test_try_map_all_parallel() {
+        let x: Vec<i32> = (0..1000).collect();
+        assert_eq!(x.clone().try_map_all_parallel(4, Ok::<_, ()>), Ok(x.clone()));
+        let calls = AtomicUsize::new(0);
+        let result = x.try_map_all_parallel(4, |x| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            if x % 300 == 299 { Err(x) } else { Ok(x) }
+        });
+        assert_eq!(result, Err(vec![299, 599, 899]));
+        assert_eq!(calls.load(Ordering::Relaxed), 1000);
+        assert_eq!(Vec::<i32>::new().try_map_all_parallel(4, Err::<(), _>), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_try_map_mut_parallel() {
+        let mut x: Vec<u32> = (0..100).collect();
+        assert_eq!(x.try_map_mut_parallel(4, |x| { *x *= 2; Ok::<_, ()>(()) }), Ok(()));
+        assert_eq!(x, (0..100).map(|x| x * 2).collect::<Vec<_>>());
+        assert_eq!(x[..3].try_map_mut_parallel(200, |x| { *x += 1; Ok::<_, ()>(()) }), Ok(()));
+        assert_eq!(x[..3], [1, 3, 5]);
+        assert_eq!(Vec::<u32>::new().try_map_mut_parallel(4, |_| Err(())), Ok(()));
+    }
+
+    #[test]
+    fn test_try_map_mut_parallel_lowest_index_error() {
+        let mut x: Vec<u32> = (0..1000).collect();
+        let result = x.try_map_mut_parallel(4, |x| if *x % 300 == 299 { Err(*x) } else { *x += 1000; Ok(()) });
+        assert_eq!(result, Err(299));
+        assert!(x[..299].iter().all(|&x| x >= 1000));
+        assert_eq!(x[299], 299);
+    }
+
+    #[test]
+    fn test_flip_parallel() {
+        let offset = 10;
+        let x: Vec<Box<dyn FnOnce() -> Result<i32, &'static str> + Send>> =
+            vec![Box::new(|| Ok(1 + offset)), Box::new(|| Err("oh noes")), Box::new(|| Err("oh foes"))];
+        assert_eq!(x.flip_parallel(3), Err("oh noes"));
+        let x = vec![|| Ok::<_, ()>(1), || Ok(2)];
+        assert_eq!(x.flip_parallel(2), Ok(vec![1, 2]));
+        let x: Vec<Box<dyn FnOnce() -> Result<i32, &'static str> + Send>> =
+            vec![Box::new(|| Err("oh noes")), Box::new(|| Ok(2)), Box::new(|| Err("oh foes"))];
+        assert_eq!(x.flip_all_parallel(2), Err(vec!["oh noes", "oh foes"]));
+    }
+}
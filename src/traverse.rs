@@ -0,0 +1,117 @@
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+use {Flippable, TryFunctor};
+
+/// A container that can be traversed with a fallible function
+///
+/// Traversing maps every element and collects the results in one pass,
+/// surfacing the first error. It's the same operation as `TryFunctor::try_fmap`
+/// under its traditional name, and is implemented for every `TryFunctor`, so
+/// generic code can be written over "any traversable container".
+pub trait Traverse: TryFunctor {
+
+    /// Apply a fallible function to every element, collecting the results
+    fn traverse<U, E, F>(self, f: F) -> Result<Self::Mapped<U>, E> where
+        Self: Sized,
+        F: FnMut(Self::Item) -> Result<U, E>
+    {
+        self.try_fmap(f)
+    }
+
+}
+
+impl<C: TryFunctor> Traverse for C {}
+
+/// A container of `Result`s that can be turned into a `Result` of a container
+///
+/// This is the generalized `flip`: for any traversable container,
+/// `c.traverse(f)` is equivalent to `c.map(f).sequence()`. It's implemented for
+/// every `Flippable` whose output is a `Result`, and `sequence` is `flip` under
+/// its traditional name, so new containers only need to implement `Flippable`.
+pub trait Sequence {
+    /// The container holding the successful values
+    type Output;
+    /// The error type
+    type Error;
+
+    /// Surface the first error, or return the container of values
+    fn sequence(self) -> Result<Self::Output, Self::Error>;
+}
+
+impl<C, T, E> Sequence for C where
+    C: Flippable<Output = Result<T, E>>
+{
+    type Output = T;
+    type Error = E;
+
+    fn sequence(self) -> Result<T, E> {
+        self.flip()
+    }
+}
+
+/// Flipping a map surfaces the error of the first failing entry in iteration order
+#[cfg(feature = "alloc")]
+impl<K: Ord, V, E> Flippable for BTreeMap<K, Result<V, E>> {
+    type Item = V;
+    type Output = Result<BTreeMap<K, V>, E>;
+
+    fn flip(self) -> Result<BTreeMap<K, V>, E> {
+        self.try_fmap(|r| r)
+    }
+}
+
+/// Flipping a map surfaces the error of the first failing entry in iteration order
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, E, S: BuildHasher + Default> Flippable for HashMap<K, Result<V, E>, S> {
+    type Item = V;
+    type Output = Result<HashMap<K, V, S>, E>;
+
+    fn flip(self) -> Result<HashMap<K, V, S>, E> {
+        self.try_fmap(|r| r)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use {Flippable, Sequence, Traverse};
+
+    fn double_positive(x: i32) -> Result<i32, String> {
+        if x > 0 { Ok(x * 2) } else { Err(format!("{} is not positive", x)) }
+    }
+
+    fn traverse_then_sequence<C>(c: C) -> bool where
+        C: Traverse<Item = i32> + Clone,
+        C::Mapped<Result<i32, String>>: Sequence<Output = C::Mapped<i32>, Error = String>,
+        C::Mapped<i32>: PartialEq,
+    {
+        c.clone().traverse(double_positive) == c.traverse(|x| Ok::<_, String>(double_positive(x))).unwrap().sequence()
+    }
+
+    #[test]
+    fn test_traverse_sequence_law() {
+        assert!(traverse_then_sequence(Some(1)));
+        assert!(traverse_then_sequence([1, -2, 3]));
+        assert!(traverse_then_sequence(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_sequence_maps() {
+        let mut m = BTreeMap::new();
+        m.insert("a", Ok(1));
+        m.insert("b", Err("oh noes"));
+        assert_eq!(m.clone().sequence(), Err("oh noes"));
+        assert_eq!(m.flip(), Err("oh noes"));
+        let mut m = HashMap::new();
+        m.insert("a", "1");
+        m.insert("b", "2");
+        let m = m.traverse(|s| s.parse::<i32>()).unwrap();
+        assert_eq!((m["a"], m["b"]), (1, 2));
+    }
+}
@@ -0,0 +1,203 @@
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+
+/// A `Result`-like type that accumulates errors instead of short-circuiting
+///
+/// Combining two `Validated` values with `zip` or `and` keeps the errors of
+/// both sides, so all the failures of a validation can be reported at once.
+/// This is the type-level companion of `flip_all`.
+///
+/// The `Errors` variant is expected to contain at least one error. The errors
+/// are kept in a `Vec`, so that `zip` and `and` report them in the order of
+/// the operands; `into_result_collect` converts them into another container,
+/// e.g. a `BTreeSet` for deduplication, once the validation is done.
+///
+/// # Type parameters
+///
+/// - `T`: The value type
+/// - `E`: The error type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Validated<T, E> {
+    /// A valid value
+    Ok(T),
+    /// All the errors encountered
+    Errors(Vec<E>),
+}
+
+impl<T, E> Validated<T, E> {
+
+    /// Construct a `Validated` containing a single error
+    pub fn error(e: E) -> Self {
+        Validated::Errors(vec![e])
+    }
+
+    /// Returns `true` if the value is valid
+    pub fn is_ok(&self) -> bool {
+        match *self {
+            Validated::Ok(_) => true,
+            Validated::Errors(_) => false,
+        }
+    }
+
+    /// Returns `true` if the value contains errors
+    pub fn is_errors(&self) -> bool {
+        !self.is_ok()
+    }
+
+    /// Map the valid value, leaving the errors untouched
+    pub fn map<U, F>(self, f: F) -> Validated<U, E> where
+        F: FnOnce(T) -> U
+    {
+        match self {
+            Validated::Ok(t) => Validated::Ok(f(t)),
+            Validated::Errors(es) => Validated::Errors(es),
+        }
+    }
+
+    /// Map every error, leaving the valid value untouched
+    pub fn map_err<E2, F>(self, f: F) -> Validated<T, E2> where
+        F: FnMut(E) -> E2
+    {
+        match self {
+            Validated::Ok(t) => Validated::Ok(t),
+            Validated::Errors(es) => Validated::Errors(es.into_iter().map(f).collect()),
+        }
+    }
+
+    /// Combine two values into a pair, accumulating the errors of both
+    pub fn zip<U>(self, other: Validated<U, E>) -> Validated<(T, U), E> {
+        self.zip_with(other, |t, u| (t, u))
+    }
+
+    /// Combine two values with a function, accumulating the errors of both
+    pub fn zip_with<U, R, F>(self, other: Validated<U, E>, f: F) -> Validated<R, E> where
+        F: FnOnce(T, U) -> R
+    {
+        match (self, other) {
+            (Validated::Ok(t), Validated::Ok(u)) => Validated::Ok(f(t, u)),
+            (Validated::Ok(_), Validated::Errors(es)) => Validated::Errors(es),
+            (Validated::Errors(es), Validated::Ok(_)) => Validated::Errors(es),
+            (Validated::Errors(mut es), Validated::Errors(more)) => {
+                es.extend(more);
+                Validated::Errors(es)
+            },
+        }
+    }
+
+    /// Return `other` if both values are valid, accumulating the errors of both otherwise
+    pub fn and<U>(self, other: Validated<U, E>) -> Validated<U, E> {
+        self.zip_with(other, |_, u| u)
+    }
+
+    /// Chain a validation that depends on the valid value
+    ///
+    /// Unlike `zip` and `and`, this can't accumulate: if `self` contains errors,
+    /// `f` isn't called.
+    pub fn and_then<U, F>(self, f: F) -> Validated<U, E> where
+        F: FnOnce(T) -> Validated<U, E>
+    {
+        match self {
+            Validated::Ok(t) => f(t),
+            Validated::Errors(es) => Validated::Errors(es),
+        }
+    }
+
+    /// Convert into a `Result` carrying all the errors
+    pub fn into_result(self) -> Result<T, Vec<E>> {
+        match self {
+            Validated::Ok(t) => Ok(t),
+            Validated::Errors(es) => Err(es),
+        }
+    }
+
+    /// Like `into_result`, but collects the errors into a container of the caller's choice
+    pub fn into_result_collect<C>(self) -> Result<T, C> where
+        C: Default + Extend<E>
+    {
+        match self {
+            Validated::Ok(t) => Ok(t),
+            Validated::Errors(es) => {
+                let mut errors = C::default();
+                errors.extend(es);
+                Err(errors)
+            },
+        }
+    }
+
+    /// Convert into an `Option`, discarding the errors
+    pub fn ok(self) -> Option<T> {
+        self.into_result().ok()
+    }
+}
+
+impl<T, E> From<Result<T, E>> for Validated<T, E> {
+    fn from(r: Result<T, E>) -> Self {
+        match r {
+            Ok(t) => Validated::Ok(t),
+            Err(e) => Validated::error(e),
+        }
+    }
+}
+
+impl<T, E> From<Validated<T, E>> for Result<T, Vec<E>> {
+    fn from(v: Validated<T, E>) -> Self {
+        v.into_result()
+    }
+}
+
+impl<T, E> FromIterator<Validated<T, E>> for Validated<Vec<T>, E> {
+    fn from_iter<I: IntoIterator<Item = Validated<T, E>>>(iter: I) -> Self {
+        let mut result_vec = Vec::new();
+        let mut errors = Vec::new();
+        for v in iter {
+            match v {
+                Validated::Ok(t) => if errors.is_empty() { result_vec.push(t) },
+                Validated::Errors(es) => errors.extend(es),
+            }
+        }
+        if errors.is_empty() {
+            Validated::Ok(result_vec)
+        } else {
+            Validated::Errors(errors)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use Validated;
+
+    fn positive(x: i32) -> Validated<i32, String> {
+        if x > 0 { Validated::Ok(x) } else { Validated::error(format!("{} is not positive", x)) }
+    }
+
+    #[test]
+    fn test_validated_zip_1() {
+        let v = positive(1).zip(positive(2)).map(|(a, b)| a + b);
+        assert_eq!(v, Validated::Ok(3));
+    }
+
+    #[test]
+    fn test_validated_zip_2() {
+        let v = positive(-1).zip(positive(2)).and(positive(-3));
+        assert_eq!(v.into_result(), Err(vec!["-1 is not positive".to_string(), "-3 is not positive".to_string()]));
+        let v = positive(-1).and(positive(-1));
+        assert_eq!(v.into_result_collect::<BTreeSet<_>>().map_err(|es| es.len()), Err(1));
+    }
+
+    #[test]
+    fn test_validated_collect() {
+        let v: Validated<Vec<i32>, String> = vec![1, -2, 3, -4].into_iter().map(positive).collect();
+        assert_eq!(v, Validated::Errors(vec!["-2 is not positive".to_string(), "-4 is not positive".to_string()]));
+        let v: Validated<Vec<i32>, String> = vec![1, 2].into_iter().map(positive).collect();
+        assert_eq!(v, Validated::Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_validated_from_result() {
+        let v: Validated<i32, &'static str> = Err("oh noes").into();
+        assert_eq!(Result::from(v), Err(vec!["oh noes"]));
+    }
+}
@@ -0,0 +1,165 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use ZipMapError;
+
+/// Extend `Option` with fallible methods that combine two optional values.
+///
+/// `try_zip_with` combines the values of two `Option`s with a fallible function.
+/// `flip_zip_with` does the same for `Option<Result>`s, such as the outcomes of
+/// two earlier fallible steps: their errors may have different types, which are
+/// unified into the output error via `Into`.
+///
+/// # Type parameters
+///
+/// - `T`: The value type of `self`
+pub trait FallibleZipExt<T> {
+
+    /// Combine the values with a fallible function if both are `Some`
+    fn try_zip_with<U, R, E, F>(self, other: Option<U>, f: F) -> Result<Option<R>, E> where
+        F: FnOnce(T, U) -> Result<R, E>;
+
+}
+
+impl<T> FallibleZipExt<T> for Option<T> {
+    fn try_zip_with<U, R, E, F>(self, other: Option<U>, f: F) -> Result<Option<R>, E> where
+        F: FnOnce(T, U) -> Result<R, E>
+    {
+        match (self, other) {
+            (Some(t), Some(u)) => f(t, u).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Extend `Option<Result<T>>` with a `flip_zip_with` method; see `FallibleZipExt`.
+///
+/// An error on either side is returned, converted, even if the other side is
+/// `None`. `self` is checked first.
+///
+/// # Type parameters
+///
+/// - `T`: The value type of `self`
+/// - `E1`: The error type of `self`
+pub trait FlipZipExt<T, E1> {
+
+    /// Flip both sides and combine their values with a fallible function if both are `Some`
+    fn flip_zip_with<U, E2, R, E, F>(self, other: Option<Result<U, E2>>, f: F) -> Result<Option<R>, E> where
+        E1: Into<E>,
+        E2: Into<E>,
+        F: FnOnce(T, U) -> Result<R, E>;
+
+}
+
+impl<T, E1> FlipZipExt<T, E1> for Option<Result<T, E1>> {
+    fn flip_zip_with<U, E2, R, E, F>(self, other: Option<Result<U, E2>>, f: F) -> Result<Option<R>, E> where
+        E1: Into<E>,
+        E2: Into<E>,
+        F: FnOnce(T, U) -> Result<R, E>
+    {
+        let t = self.transpose().map_err(Into::into)?;
+        let u = other.transpose().map_err(Into::into)?;
+        t.try_zip_with(u, f)
+    }
+}
+
+/// Extend `Vec` with a `try_zip_map` method that combines two `Vec`s element-wise
+/// with a fallible function.
+///
+/// Unlike `zip`, which silently stops at the end of the shorter side, the
+/// lengths must be equal: a mismatch is reported as
+/// `ZipMapError::LengthMismatch` before the function is called at all. The
+/// combination stops at the first error.
+///
+/// # Type parameters
+///
+/// - `T`: The value type of `self`
+#[cfg(feature = "alloc")]
+pub trait FallibleZipMapExt<T> {
+
+    /// Combine the elements pairwise with a fallible function, requiring equal lengths
+    fn try_zip_map<U, R, E, F>(self, other: Vec<U>, f: F) -> Result<Vec<R>, ZipMapError<E>> where
+        F: FnMut(T, U) -> Result<R, E>;
+
+}
+
+#[cfg(feature = "alloc")]
+impl<T> FallibleZipMapExt<T> for Vec<T> {
+    fn try_zip_map<U, R, E, F>(self, other: Vec<U>, mut f: F) -> Result<Vec<R>, ZipMapError<E>> where
+        F: FnMut(T, U) -> Result<R, E>
+    {
+        if self.len() != other.len() {
+            return Err(ZipMapError::LengthMismatch { left: self.len(), right: other.len() });
+        }
+        self.into_iter().zip(other).map(|(t, u)| f(t, u).map_err(ZipMapError::Map)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::{ParseFloatError, ParseIntError};
+
+    use {FallibleZipExt, FlipZipExt};
+    #[cfg(feature = "std")]
+    use {FallibleZipMapExt, ZipMapError};
+
+    #[derive(Debug, PartialEq)]
+    enum MyError {
+        Int(ParseIntError),
+        Float(ParseFloatError),
+        Negative,
+    }
+
+    impl From<ParseIntError> for MyError {
+        fn from(e: ParseIntError) -> MyError {
+            MyError::Int(e)
+        }
+    }
+
+    impl From<ParseFloatError> for MyError {
+        fn from(e: ParseFloatError) -> MyError {
+            MyError::Float(e)
+        }
+    }
+
+    #[test]
+    fn test_try_zip_with() {
+        let div = |a: i32, b: i32| a.checked_div(b).ok_or("division by zero");
+        assert_eq!(Some(6).try_zip_with(Some(3), div), Ok(Some(2)));
+        assert_eq!(Some(6).try_zip_with(None, div), Ok(None));
+        assert_eq!(Some(6).try_zip_with(Some(0), div), Err("division by zero"));
+    }
+
+    #[test]
+    fn test_flip_zip_with() {
+        let scale = |n: i32, x: f64| if n >= 0 { Ok(n as f64 * x) } else { Err(MyError::Negative) };
+        let zip = |a: Option<&str>, b: Option<&str>| a.map(str::parse::<i32>).flip_zip_with(b.map(str::parse::<f64>), scale);
+        assert_eq!(zip(Some("2"), Some("1.5")), Ok(Some(3.0)));
+        assert_eq!(zip(Some("2"), None), Ok(None));
+        assert_eq!(zip(Some("-2"), Some("1.5")), Err(MyError::Negative));
+        assert!(matches!(zip(None, Some("x")), Err(MyError::Float(_))));
+        assert!(matches!(zip(Some("x"), Some("y")), Err(MyError::Int(_))));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_try_zip_map() {
+        let div = |a: i32, b: i32| a.checked_div(b).ok_or("division by zero");
+        assert_eq!(vec![6, 9].try_zip_map(vec![3, 3], div), Ok(vec![2, 3]));
+        assert_eq!(vec![6, 9].try_zip_map(vec![3, 0], div), Err(ZipMapError::Map("division by zero")));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_try_zip_map_length_mismatch() {
+        let mut calls = 0;
+        let x = vec![6, 9].try_zip_map(vec![3], |a: i32, b: i32| {
+            calls += 1;
+            Ok::<_, &str>(a * b)
+        });
+        assert_eq!(x, Err(ZipMapError::LengthMismatch { left: 2, right: 1 }));
+        assert_eq!(calls, 0);
+        assert_eq!(x.unwrap_err().to_string(), "length mismatch: 2 and 1 elements");
+    }
+}
@@ -0,0 +1,549 @@
+//! `#[derive(TryMap)]` for the `try_map` crate
+//!
+//! Don't depend on this crate directly: enable the `derive` feature of
+//! `try_map`, which re-exports the derive macro along with its documentation.
+//!
+//! The crate has no dependencies, so the input is read by a small hand-written
+//! parser that only understands what the derive needs, and the output is
+//! generated as a string.
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, Group, TokenStream, TokenTree};
+use std::mem;
+
+/// Implement `try_map::TryFunctor` over the first type parameter of a struct or an enum
+///
+/// See the documentation of the re-export in `try_map`.
+#[proc_macro_derive(TryMap, attributes(try_map))]
+pub fn derive_try_map(input: TokenStream) -> TokenStream {
+    let output = match expand(input) {
+        Ok(output) => output,
+        Err(message) => format!("compile_error!({:?});", message),
+    };
+    output.parse().expect("the generated code should be valid tokens")
+}
+
+/// A generic parameter of the input type
+struct Param {
+    /// The declaration, with the bounds but without the default
+    decl: String,
+    /// The parameter as an argument of the type, e.g. `'a` or `T`
+    name: String,
+    is_type: bool,
+    has_bounds: bool,
+}
+
+/// The fields of a struct or of an enum variant
+enum Fields {
+    Named(Vec<(String, Vec<TokenTree>)>),
+    Unnamed(Vec<Vec<TokenTree>>),
+    Unit,
+}
+
+/// The fields of a struct, or the variants of an enum
+enum Body {
+    Struct(Fields),
+    Enum(Vec<(String, Fields)>),
+}
+
+/// The parts of the input item that the derive needs
+struct Item {
+    name: String,
+    params: Vec<Param>,
+    where_clause: Vec<TokenTree>,
+    body: Body,
+    /// Whether `#[try_map(all_errors)]` asks for a `try_fmap_all` method
+    all_errors: bool,
+}
+
+fn expand(input: TokenStream) -> Result<String, String> {
+    let item = parse_item(input)?;
+    let param = item.params.iter().find(|p| p.is_type)
+        .ok_or_else(|| format!("`#[derive(TryMap)]` needs a type parameter to map, and `{}` has none", item.name))?;
+    if param.has_bounds {
+        return Err(format!("the mapped type parameter `{}` can't have bounds, as `TryFunctor::Mapped` can't require them", param.name));
+    }
+    if let Some(predicate) = split_commas(&item.where_clause).iter().find(|p| mentions(p, &param.name)) {
+        return Err(format!(
+            "the mapped type parameter `{}` can't appear in the where clause, as in `{}`, since `TryFunctor::Mapped` can't require it",
+            param.name, to_string(predicate),
+        ));
+    }
+
+    let mut generator = Generator { param: &param.name, fresh: 0 };
+    let body = generator.convert_body(&item, false)?;
+
+    let generics = item.params.iter().map(|p| &*p.decl).collect::<Vec<_>>().join(", ");
+    let args = item.params.iter().map(|p| &*p.name).collect::<Vec<_>>().join(", ");
+    let mapped_args = item.params.iter()
+        .map(|p| if p.name == param.name { "__U" } else { &*p.name })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let where_clause = if item.where_clause.is_empty() {
+        String::new()
+    } else {
+        format!("where {}", to_string(&item.where_clause))
+    };
+    let try_fmap_all = if item.all_errors {
+        format!("
+            impl<{generics}> {name}<{args}> {where_clause} {{
+                /// Like `TryFunctor::try_fmap`, but collects the errors of all the fields instead of stopping at the first one
+                pub fn try_fmap_all<__U, __E, __F>(self, mut __f: __F) -> ::try_map::__private::Result<{name}<{mapped_args}>, ::try_map::__private::Vec<__E>> where
+                    __F: ::try_map::__private::FnMut({param}) -> ::try_map::__private::Result<__U, __E>
+                {{
+                    let __f: &mut ::try_map::__private::DynFnMut<{param}, __U, __E> = &mut __f;
+                    {body}
+                }}
+            }}",
+            generics = generics,
+            name = item.name,
+            args = args,
+            where_clause = where_clause,
+            param = param.name,
+            mapped_args = mapped_args,
+            body = generator.convert_body(&item, true)?,
+        )
+    } else {
+        String::new()
+    };
+    Ok(format!("
+        impl<{generics}> ::try_map::TryFunctor for {name}<{args}> {where_clause} {{
+            type Item = {param};
+            type Mapped<__U> = {name}<{mapped_args}>;
+
+            fn try_fmap<__U, __E, __F>(self, mut __f: __F) -> ::try_map::__private::Result<{name}<{mapped_args}>, __E> where
+                __F: ::try_map::__private::FnMut({param}) -> ::try_map::__private::Result<__U, __E>
+            {{
+                // Erasing the closure type keeps the instantiations finite for recursive types
+                let __f: &mut ::try_map::__private::DynFnMut<{param}, __U, __E> = &mut __f;
+                ::try_map::__private::Result::Ok({body})
+            }}
+        }}
+        {try_fmap_all}",
+        generics = generics,
+        name = item.name,
+        args = args,
+        where_clause = where_clause,
+        param = param.name,
+        mapped_args = mapped_args,
+        body = body,
+        try_fmap_all = try_fmap_all,
+    ))
+}
+
+/// Generates the expressions that convert the fields
+struct Generator<'a> {
+    param: &'a str,
+    fresh: usize,
+}
+
+impl<'a> Generator<'a> {
+
+    fn fresh_name(&mut self) -> String {
+        self.fresh += 1;
+        format!("__x{}", self.fresh)
+    }
+
+    /// Generate the body of `try_fmap`, which evaluates to the converted value,
+    /// or of `try_fmap_all`, which evaluates to the `Result`
+    fn convert_body(&mut self, item: &Item, all_errors: bool) -> Result<String, String> {
+        match item.body {
+            Body::Struct(ref fields) => {
+                let (pattern, value) = self.convert_fields(&item.name, fields, all_errors)?;
+                Ok(format!("{{ let {} = self; {} }}", pattern, value))
+            },
+            Body::Enum(ref variants) => {
+                let mut arms = Vec::new();
+                for (variant, fields) in variants {
+                    let (pattern, value) = self.convert_fields(&format!("{}::{}", item.name, variant), fields, all_errors)?;
+                    arms.push(format!("{} => {},", pattern, value));
+                }
+                Ok(format!("match self {{ {} }}", arms.concat()))
+            },
+        }
+    }
+
+    /// Generate the pattern that destructures the fields of `path`, and the
+    /// expression that rebuilds it from the converted fields
+    ///
+    /// With `all_errors`, every field is converted even if another one failed,
+    /// and the expression evaluates to a `Result` with all the errors.
+    fn convert_fields(&mut self, path: &str, fields: &Fields, all_errors: bool) -> Result<(String, String), String> {
+        let types: Vec<&[TokenTree]> = match *fields {
+            Fields::Named(ref fields) => fields.iter().map(|(_, ty)| &ty[..]).collect(),
+            Fields::Unnamed(ref types) => types.iter().map(|ty| &ty[..]).collect(),
+            Fields::Unit => Vec::new(),
+        };
+        let bindings: Vec<String> = (0..types.len()).map(|i| format!("__{}", i)).collect();
+        let mut values = Vec::new();
+        // With `all_errors`: the statements converting the fields, and the fields they convert
+        let (mut statements, mut converted) = (Vec::new(), Vec::new());
+        for (ty, binding) in types.iter().zip(&bindings) {
+            let value = self.convert(ty, binding)?;
+            if all_errors && value != *binding {
+                statements.push(format!(
+                    "let {} = ::try_map::__private::collect_error(::try_map::__private::catch(|| -> ::try_map::__private::Result<_, __E> {{ {} }}), &mut __errors);",
+                    binding, into_result(&value),
+                ));
+                converted.push(&binding[..]);
+                values.push(binding.clone());
+            } else {
+                values.push(value);
+            }
+        }
+        let (pattern, value) = match *fields {
+            Fields::Named(ref fields) => {
+                let names = fields.iter().map(|(name, _)| name);
+                let patterns: Vec<String> = names.clone().zip(&bindings).map(|(name, binding)| format!("{}: {}", name, binding)).collect();
+                let values: Vec<String> = names.zip(&values).map(|(name, value)| format!("{}: {}", name, value)).collect();
+                (format!("{} {{ {} }}", path, patterns.join(", ")), format!("{} {{ {} }}", path, values.join(", ")))
+            },
+            Fields::Unnamed(_) => (format!("{}({})", path, bindings.join(", ")), format!("{}({})", path, values.join(", "))),
+            Fields::Unit => (path.to_string(), path.to_string()),
+        };
+        if !all_errors {
+            return Ok((pattern, value));
+        }
+        if converted.is_empty() {
+            return Ok((pattern, format!("::try_map::__private::Result::Ok({})", value)));
+        }
+        let somes: Vec<String> = converted.iter().map(|binding| format!("::try_map::__private::Option::Some({})", binding)).collect();
+        Ok((pattern, format!(
+            "{{ let mut __errors = ::try_map::__private::Vec::new(); {} match ({},) {{ ({},) => ::try_map::__private::Result::Ok({}), _ => ::try_map::__private::Result::Err(__errors) }} }}",
+            statements.concat(), converted.join(", "), somes.join(", "), value,
+        )))
+    }
+
+    /// Generate an expression that converts `expr`, of type `ty`, with the closure `__f`
+    fn convert(&mut self, ty: &[TokenTree], expr: &str) -> Result<String, String> {
+        if !mentions(ty, self.param) {
+            return Ok(expr.to_string());
+        }
+        if ty.len() == 1 {
+            match ty[0] {
+                TokenTree::Ident(_) => return Ok(format!("__f({})?", expr)),
+                TokenTree::Group(ref g) => {
+                    let inner: Vec<TokenTree> = g.stream().into_iter().collect();
+                    match g.delimiter() {
+                        Delimiter::Parenthesis => return self.convert_tuple(&inner, expr),
+                        Delimiter::Bracket => {
+                            let end = top_level_position(&inner, ';').unwrap_or(inner.len());
+                            return self.convert_elements(&inner[..end], expr);
+                        },
+                        Delimiter::None => return self.convert(&inner, expr),
+                        Delimiter::Brace => {},
+                    }
+                },
+                _ => {},
+            }
+        }
+        let start = ty.iter().position(|tt| is_punct(tt, '<'));
+        let container = match start {
+            Some(start) if start > 0 => ty[start - 1].to_string(),
+            _ => String::new(),
+        };
+        if container == "PhantomData" {
+            return Ok("::try_map::__private::PhantomData".to_string());
+        }
+        let arguments = generic_arguments(ty).unwrap_or_default();
+        let mut mentioning = arguments.iter().enumerate().filter(|&(_, arg)| mentions(arg, self.param));
+        match (mentioning.next(), mentioning.next()) {
+            (Some((i, element)), None) => {
+                // The containers of the standard library whose mapped element isn't the only argument
+                let mapped = match &*container {
+                    "Result" => Some((0, "the `Ok` value")),
+                    "BTreeMap" | "HashMap" => Some((1, "the values")),
+                    "ControlFlow" => Some((1, "the `Continue` payload")),
+                    _ => None,
+                };
+                match mapped {
+                    Some((position, what)) if position != i => Err(format!(
+                        "`#[derive(TryMap)]` can't map `{}` in a field of type `{}`, as `TryFunctor` only maps {} of a `{}`",
+                        self.param, to_string(ty), what, container,
+                    )),
+                    _ => self.convert_elements(element, expr),
+                }
+            },
+            _ => Err(format!(
+                "`#[derive(TryMap)]` can't map a field of type `{}`: `{}` must be the type of the field, of the elements of a `TryFunctor` container, or of a tuple",
+                to_string(ty), self.param,
+            )),
+        }
+    }
+
+    /// Generate an expression that converts `expr`, a tuple of the types in `inner`
+    fn convert_tuple(&mut self, inner: &[TokenTree], expr: &str) -> Result<String, String> {
+        if top_level_position(inner, ',').is_none() {
+            return self.convert(inner, expr);
+        }
+        let (mut names, mut values) = (Vec::new(), Vec::new());
+        for ty in split_commas(inner) {
+            let name = self.fresh_name();
+            values.push(self.convert(&ty, &name)?);
+            names.push(name);
+        }
+        Ok(format!("{{ let ({},) = {}; ({},) }}", names.join(", "), expr, values.join(", ")))
+    }
+
+    /// Generate an expression that converts `expr`, a `TryFunctor` container of `element`s
+    fn convert_elements(&mut self, element: &[TokenTree], expr: &str) -> Result<String, String> {
+        if element.len() == 1 && is_ident(&element[0], self.param) {
+            return Ok(format!("::try_map::TryFunctor::try_fmap({}, &mut *__f)?", expr));
+        }
+        let name = self.fresh_name();
+        let value = self.convert(element, &name)?;
+        Ok(format!(
+            "::try_map::TryFunctor::try_fmap({}, |{}| -> ::try_map::__private::Result<_, __E> {{ {} }})?",
+            expr, name, into_result(&value),
+        ))
+    }
+}
+
+/// Turn a conversion, which uses `?`, into an expression of the `Result`
+fn into_result(value: &str) -> String {
+    // A single call of the closure or of `try_fmap` already returns the `Result`
+    if value.starts_with("::try_map::TryFunctor::try_fmap(") || value.starts_with("__f(") {
+        value.trim_end_matches('?').to_string()
+    } else {
+        format!("::try_map::__private::Result::Ok({})", value)
+    }
+}
+
+fn parse_item(input: TokenStream) -> Result<Item, String> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let all_errors = parse_options(&tokens)?;
+    let tokens = skip_attributes_and_visibility(&tokens);
+    let is_enum = match tokens.first() {
+        Some(tt) if is_ident(tt, "struct") => false,
+        Some(tt) if is_ident(tt, "enum") => true,
+        _ => return Err("`#[derive(TryMap)]` only supports structs and enums".to_string()),
+    };
+    let name = match tokens.get(1) {
+        Some(TokenTree::Ident(name)) => name.to_string(),
+        _ => return Err("expected the name of the type".to_string()),
+    };
+
+    // The generic parameters, between the outermost angle brackets
+    let mut i = 2;
+    let mut generics = Vec::new();
+    if tokens.get(i).map_or(false, |tt| is_punct(tt, '<')) {
+        let mut depth = 0usize;
+        let mut after_dash = false;
+        loop {
+            let tt = tokens.get(i).ok_or("unclosed generic parameters")?;
+            i += 1;
+            if is_punct(tt, '<') {
+                depth += 1;
+            } else if is_punct(tt, '>') && !after_dash {
+                depth -= 1;
+            }
+            if depth == 0 {
+                break;
+            }
+            after_dash = is_punct(tt, '-');
+            if !(depth == 1 && is_punct(tt, '<')) {
+                generics.push(tt.clone());
+            }
+        }
+    }
+
+    // The body and the where clause, which comes before a braced body and after a parenthesized one
+    let mut body = None;
+    let mut where_clause = Vec::new();
+    let mut in_where = false;
+    for tt in &tokens[i..] {
+        match *tt {
+            TokenTree::Group(ref g) if g.delimiter() == Delimiter::Brace => {
+                body = Some(g.clone());
+                break;
+            },
+            TokenTree::Group(ref g) if g.delimiter() == Delimiter::Parenthesis && !in_where => body = Some(g.clone()),
+            _ if is_ident(tt, "where") => in_where = true,
+            _ if is_punct(tt, ';') => break,
+            _ if in_where => where_clause.push(tt.clone()),
+            _ => {},
+        }
+    }
+
+    let body = match body {
+        Some(ref body) if is_enum => Body::Enum(split_commas(&body.stream().into_iter().collect::<Vec<_>>()).iter()
+            .map(|variant| parse_variant(variant))
+            .collect::<Result<_, _>>()?),
+        Some(ref body) => Body::Struct(parse_fields(body)),
+        None => Body::Struct(Fields::Unit),
+    };
+    Ok(Item {
+        name,
+        params: split_commas(&generics).iter().map(|p| parse_param(p)).collect(),
+        where_clause,
+        body,
+        all_errors,
+    })
+}
+
+/// Read the `#[try_map(...)]` attributes of the item, returning whether `all_errors` is set
+fn parse_options(tokens: &[TokenTree]) -> Result<bool, String> {
+    let mut all_errors = false;
+    let mut i = 0;
+    while i + 1 < tokens.len() && is_punct(&tokens[i], '#') {
+        if let TokenTree::Group(ref attribute) = tokens[i + 1] {
+            let attribute: Vec<TokenTree> = attribute.stream().into_iter().collect();
+            if let [ref path, TokenTree::Group(ref options)] = attribute[..] {
+                if is_ident(path, "try_map") {
+                    for option in split_commas(&options.stream().into_iter().collect::<Vec<_>>()) {
+                        match &*to_string(&option) {
+                            "all_errors" => all_errors = true,
+                            option => return Err(format!("unknown `try_map` option `{}`, expected `all_errors`", option)),
+                        }
+                    }
+                }
+            }
+        }
+        i += 2;
+    }
+    Ok(all_errors)
+}
+
+fn parse_param(tokens: &[TokenTree]) -> Param {
+    let tokens = skip_attributes_and_visibility(tokens);
+    let end = top_level_position(tokens, '=').unwrap_or(tokens.len());
+    let decl = to_string(&tokens[..end]);
+    if is_punct(&tokens[0], '\'') {
+        Param { decl, name: to_string(&tokens[..2]), is_type: false, has_bounds: false }
+    } else if is_ident(&tokens[0], "const") {
+        Param { decl, name: tokens[1].to_string(), is_type: false, has_bounds: false }
+    } else {
+        let has_bounds = tokens.get(1).map_or(false, |tt| is_punct(tt, ':'));
+        Param { decl, name: tokens[0].to_string(), is_type: true, has_bounds }
+    }
+}
+
+fn parse_variant(tokens: &[TokenTree]) -> Result<(String, Fields), String> {
+    let tokens = skip_attributes_and_visibility(tokens);
+    let name = match tokens.first() {
+        Some(TokenTree::Ident(name)) => name.to_string(),
+        _ => return Err("expected the name of a variant".to_string()),
+    };
+    // Anything else, such as a discriminant, leaves the variant without fields
+    let fields = match tokens.get(1) {
+        Some(TokenTree::Group(g)) if g.delimiter() != Delimiter::None => parse_fields(g),
+        _ => Fields::Unit,
+    };
+    Ok((name, fields))
+}
+
+fn parse_fields(body: &Group) -> Fields {
+    let tokens: Vec<TokenTree> = body.stream().into_iter().collect();
+    let fields = split_commas(&tokens);
+    if body.delimiter() == Delimiter::Brace {
+        Fields::Named(fields.iter().map(|field| {
+            let field = skip_attributes_and_visibility(field);
+            (field[0].to_string(), field[2..].to_vec())
+        }).collect())
+    } else {
+        Fields::Unnamed(fields.iter().map(|field| skip_attributes_and_visibility(field).to_vec()).collect())
+    }
+}
+
+/// Skip the attributes and the visibility at the start of an item, a field, a variant or a generic parameter
+fn skip_attributes_and_visibility(tokens: &[TokenTree]) -> &[TokenTree] {
+    let mut i = 0;
+    while i < tokens.len() {
+        if is_punct(&tokens[i], '#') {
+            i += 2;
+        } else if is_ident(&tokens[i], "pub") {
+            i += 1;
+            // `pub(crate)` and the like, but not `pub (T, U)`, a public tuple field
+            if let Some(TokenTree::Group(g)) = tokens.get(i) {
+                let restriction = g.stream().into_iter().next();
+                if g.delimiter() == Delimiter::Parenthesis
+                    && restriction.map_or(false, |tt| ["crate", "self", "super", "in"].iter().any(|r| is_ident(&tt, r)))
+                {
+                    i += 1;
+                }
+            }
+        } else {
+            break;
+        }
+    }
+    &tokens[i.min(tokens.len())..]
+}
+
+/// Split a token list at its top-level commas, ignoring the commas between angle brackets
+fn split_commas(tokens: &[TokenTree]) -> Vec<Vec<TokenTree>> {
+    let mut parts = Vec::new();
+    let mut current = Vec::new();
+    let mut depth = 0usize;
+    let mut after_dash = false;
+    for tt in tokens {
+        if is_punct(tt, '<') {
+            depth += 1;
+        } else if is_punct(tt, '>') && !after_dash {
+            depth = depth.saturating_sub(1);
+        } else if is_punct(tt, ',') && depth == 0 {
+            parts.push(mem::take(&mut current));
+            after_dash = false;
+            continue;
+        }
+        after_dash = is_punct(tt, '-');
+        current.push(tt.clone());
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// The position of the first `c` outside of angle brackets
+fn top_level_position(tokens: &[TokenTree], c: char) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut after_dash = false;
+    for (i, tt) in tokens.iter().enumerate() {
+        if is_punct(tt, c) && depth == 0 {
+            return Some(i);
+        } else if is_punct(tt, '<') {
+            depth += 1;
+        } else if is_punct(tt, '>') && !after_dash {
+            depth = depth.saturating_sub(1);
+        }
+        after_dash = is_punct(tt, '-');
+    }
+    None
+}
+
+/// The generic arguments of a type path that ends with them, e.g. `K` and `V` for `BTreeMap<K, V>`
+fn generic_arguments(ty: &[TokenTree]) -> Option<Vec<Vec<TokenTree>>> {
+    let start = ty.iter().position(|tt| is_punct(tt, '<'))?;
+    if !is_punct(ty.last()?, '>') || top_level_position(&ty[start + 1..], '>') != Some(ty.len() - start - 2) {
+        return None;
+    }
+    Some(split_commas(&ty[start + 1..ty.len() - 1]))
+}
+
+/// Whether the identifier `name` appears anywhere in the tokens
+fn mentions(tokens: &[TokenTree], name: &str) -> bool {
+    tokens.iter().any(|tt| match *tt {
+        TokenTree::Ident(ref ident) => ident.to_string() == name,
+        TokenTree::Group(ref g) => mentions(&g.stream().into_iter().collect::<Vec<_>>(), name),
+        _ => false,
+    })
+}
+
+fn is_punct(tt: &TokenTree, c: char) -> bool {
+    match *tt {
+        TokenTree::Punct(ref p) => p.as_char() == c,
+        _ => false,
+    }
+}
+
+fn is_ident(tt: &TokenTree, name: &str) -> bool {
+    match *tt {
+        TokenTree::Ident(ref ident) => ident.to_string() == name,
+        _ => false,
+    }
+}
+
+fn to_string(tokens: &[TokenTree]) -> String {
+    tokens.iter().cloned().collect::<TokenStream>().to_string()
+}